@@ -0,0 +1,15 @@
+//! Detects the build with AddressSanitizer (`-Zsanitizer=address`), the `sanitize` cfg
+//! is unstable, so the `safemanuallydrop_asan` cfg is set instead (see `core::poison`).
+
+fn main() {
+	println!("cargo:rerun-if-changed=build.rs");
+	println!("cargo:rustc-check-cfg=cfg(safemanuallydrop_asan)");
+
+	// "address", "address,leak", ...
+	let is_asan = std::env::var("CARGO_CFG_SANITIZE")
+		.map(|a| a.split(',').any(|a| a == "address"))
+		.unwrap_or(false);
+	if is_asan {
+		println!("cargo:rustc-cfg=safemanuallydrop_asan");
+	}
+}
@@ -32,7 +32,8 @@ fn main() {
 		// <<-- PANIC
 		/*
 			Undefined behavior when using ManuallyDrop(combo_replace_manudropstate),
-			instead of the expected default state, the current state: DropModeTrig.
+			instead of the expected default state, the current state: DropModeTrig,
+			first dropped at examples/abort.rs:29 (track_location), used again at examples/abort.rs:40.
 
 			Emergency stop.
 		*/
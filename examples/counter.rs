@@ -1,10 +1,10 @@
-// Let me remind you that CounterManuallyDrop by behavior allows undefined
-// behavior in the same way as ManuallyDrop, but, unlike ManuallyDrop,
-// Counter keeps a counter of the number of undefined behavior triggers.
+// Let me remind you that CounterManuallyDrop does not stop the program in case
+// of undefined behavior, unlike the other triggers, it keeps a counter of the number
+// of undefined behavior triggers, and the invalid operation is skipped (a repeated
+// drop does nothing, a repeated take returns None with take_or_none).
 
-// !!!!
-// CounterManuallyDrop is experimental and changes the behavior of
-// the trigger trait for all types.
+// CounterManuallyDrop is a non-fatal trigger (its output is `()`), other
+// triggers (panic, abort, loop, hook) remain fatal.
 
 #[cfg(feature = "support_count_trig")]
 use SafeManuallyDrop::AutoSafeCounterManuallyDrop as ManuallyDrop;
@@ -36,23 +36,23 @@ fn main() {
 		// !!! ATTENTION !!!
 		// =================
 		// Procedure:
-		// 1. Free up memory and try to read it
-		// 2. Re-free memory
+		// 1. Free up memory
+		// 2. Re-free memory (the trigger returns, the drop is skipped)
+		// 3. Try to take the freed value (the trigger returns, None)
 		ManuallyDrop::drop(&mut data); // VALID
 		assert!(data.is_next_trig()); // VALID, counter trigger worked.
 
-		ManuallyDrop::drop(&mut data); // <<-- INVALID BEH, COUNTER += 1 (=1), COMBO DROP
+		ManuallyDrop::drop(&mut data); // <<-- INVALID BEH, COUNTER += 1 (=1), COMBO DROP, SKIPPED
+		ManuallyDrop::drop(&mut data); // <<-- INVALID BEH, COUNTER += 1 (=2), COMBO DROP, SKIPPED
 	}
 
-	// !!! Reading an already freed value
-	println!("data: {:?}", &data); // <<-- INVALID BEH, COUNTER += 1 (=2)
-
 	// to avoid warning if the always_compatible_stdapi flag is not used (can be removed)
 	#[allow(unused_unsafe)]
 	let _data2 = unsafe {
 		// <<-- INVALID BEH, COUNTER += 1 (=3)
-		// !!! Trying to get the freed value
-		ManuallyDrop::take(&mut data)
+		// !!! Trying to get the freed value, None
+		// (reading or taking the freed value with `deref`/`take` is refused with a panic)
+		ManuallyDrop::take_or_none(&mut data)
 	};
 
 	#[cfg(feature = "support_count_trig")]
@@ -25,7 +25,8 @@ fn main() {
 			/*
 				thread 'main' panicked at 'Undefined behavior when using
 				ManuallyDrop(combo_replace_manudropstate), instead of the expected default
-				state, the current state: DropModeTrig.', src/core/trig/hook.rs:14:5
+				state, the current state: DropModeTrig, first dropped at examples/easy.rs:21 (track_location),
+				used again at examples/easy.rs:30.', src/core/trig/hook.rs:14:5
 			*/
 			ManuallyDrop::drop(&mut data); // INVALID, COMBO DROP
 		}
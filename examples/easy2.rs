@@ -16,7 +16,8 @@ fn main() {
 		/*
 			thread 'main' panicked at 'Undefined behavior when using
 			ManuallyDrop(combo_replace_manudropstate), instead of the expected default
-			state, the current state: DropModeTrig.'.
+			state, the current state: DropModeTrig, first dropped at examples/easy2.rs:12 (track_location),
+				used again at examples/easy2.rs:21.'.
 		*/
 		ManuallyDrop::drop(&mut data); // INVALID, COMBO DROP
 	}
@@ -7,18 +7,16 @@ use std::ops::Deref;
 use SafeManuallyDrop::AlwaysSafeHookManuallyDrop as ManuallyDrop;
 
 fn main() {
-	unsafe {
-		ManuallyDrop::set_hook(|args| {
-			println!("!!!{:?}", args);
+	ManuallyDrop::set_hook(|args| {
+		println!("!!!{:?}", args);
 
-			for _ in 0..3 {
-				std::thread::sleep(std::time::Duration::from_millis(1000));
-			}
+		for _ in 0..3 {
+			std::thread::sleep(std::time::Duration::from_millis(1000));
+		}
 
-			println!("exit");
-			std::process::exit(0x0100);
-		});
-	}
+		println!("exit");
+		std::process::exit(0x0100);
+	});
 
 	let mut data = ManuallyDrop::new(vec![1, 2, 3, 4]);
 	println!("data: {:?}", data.deref());
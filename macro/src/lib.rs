@@ -0,0 +1,127 @@
+//! The `#[SafeManuallyDrop::test]` attribute, use it through the `test_macro`
+//! feature of SafeManuallyDrop.
+//!
+//! The attribute turns the function into a test and runs its body in a tracking
+//! scope (`SafeManuallyDrop::core::testing::run_in_test_scope`), the test fails
+//! if a violation of ManuallyDrop was detected in it (even with non-fatal triggers)
+//! or if a ManuallyDrop created in it was neither released nor ignore_dropped.
+
+#![allow(non_snake_case)]
+#![allow(clippy::tabs_in_doc_comments)]
+
+extern crate proc_macro;
+
+use proc_macro::Delimiter;
+use proc_macro::Group;
+use proc_macro::Ident;
+use proc_macro::Literal;
+use proc_macro::Punct;
+use proc_macro::Spacing;
+use proc_macro::Span;
+use proc_macro::TokenStream;
+use proc_macro::TokenTree;
+
+/// Run the test in a tracking scope of SafeManuallyDrop, the test fails
+/// on any violation of ManuallyDrop or on any ManuallyDrop that was
+/// neither released nor ignore_dropped.
+///
+/// ```rust,ignore
+/// #[SafeManuallyDrop::test]
+/// fn test_release() {
+/// 	let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+///
+/// 	unsafe {
+/// 		ManuallyDrop::drop(&mut data);
+/// 	}
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(attr: TokenStream, item: TokenStream) -> TokenStream {
+	if let Some(a) = attr.into_iter().next() {
+		return compile_error(
+			"#[SafeManuallyDrop::test] does not take arguments",
+			a.span(),
+		);
+	}
+
+	let mut signature: Vec<TokenTree> = item.into_iter().collect();
+	let body = match signature.pop() {
+		Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+		Some(a) => return compile_error("#[SafeManuallyDrop::test] expects a function", a.span()),
+		None => {
+			return compile_error(
+				"#[SafeManuallyDrop::test] expects a function",
+				Span::call_site(),
+			)
+		}
+	};
+	let is_fn = signature
+		.iter()
+		.any(|a| matches!(a, TokenTree::Ident(a) if a.to_string() == "fn"));
+	if !is_fn {
+		return compile_error("#[SafeManuallyDrop::test] expects a function", body.span());
+	}
+
+	let mut result = TokenStream::new();
+	// #[::core::prelude::v1::test]
+	result.extend([
+		TokenTree::from(Punct::new('#', Spacing::Alone)),
+		TokenTree::from(Group::new(
+			Delimiter::Bracket,
+			path(&["core", "prelude", "v1", "test"]),
+		)),
+	]);
+	result.extend(signature);
+
+	// { ::SafeManuallyDrop::core::testing::run_in_test_scope(|| body) }
+	let mut closure = TokenStream::new();
+	closure.extend([
+		TokenTree::from(Punct::new('|', Spacing::Joint)),
+		TokenTree::from(Punct::new('|', Spacing::Alone)),
+		TokenTree::from(body.clone()),
+	]);
+	let mut call = path(&["SafeManuallyDrop", "core", "testing", "run_in_test_scope"]);
+	call.extend([TokenTree::from(Group::new(Delimiter::Parenthesis, closure))]);
+
+	let mut new_body = Group::new(Delimiter::Brace, call);
+	new_body.set_span(body.span());
+	result.extend([TokenTree::from(new_body)]);
+
+	result
+}
+
+/// `::a::b::c`
+fn path(segments: &[&str]) -> TokenStream {
+	let mut result = TokenStream::new();
+	for segment in segments {
+		result.extend([
+			TokenTree::from(Punct::new(':', Spacing::Joint)),
+			TokenTree::from(Punct::new(':', Spacing::Alone)),
+			TokenTree::from(Ident::new(segment, Span::call_site())),
+		]);
+	}
+
+	result
+}
+
+/// `::core::compile_error!("message")`
+fn compile_error(message: &str, span: Span) -> TokenStream {
+	let mut result = path(&["core", "compile_error"]);
+	let mut message = Literal::string(message);
+	message.set_span(span);
+
+	result.extend([
+		TokenTree::from(Punct::new('!', Spacing::Alone)),
+		TokenTree::from(Group::new(
+			Delimiter::Parenthesis,
+			TokenStream::from(TokenTree::from(message)),
+		)),
+	]);
+	result
+		.into_iter()
+		.map(|mut a| {
+			a.set_span(span);
+			a
+		})
+		.collect()
+}
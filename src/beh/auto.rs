@@ -85,6 +85,9 @@ macro_rules! cfg_if_safemode {
 	};
 }
 
+use core::cell::Cell;
+use core::sync::atomic::AtomicU8;
+
 // TODO, MANYCOPYCODE
 cfg_if_safemode! {
 	// Unsafe
@@ -101,7 +104,7 @@ cfg_if_safemode! {
 	/// ```no_run
 	/// UnsafeManuallyDrop
 	/// ```
-	#if_not_safe(pub type AutoSafeManuallyDrop<T, Trig> = crate::beh::r#unsafe::UnsafeManuallyDrop<T, Trig>;)
+	#if_not_safe(pub type AutoSafeManuallyDrop<T, Trig, S = AtomicU8> = crate::beh::r#unsafe::UnsafeManuallyDrop<T, Trig, S>;)
 
 	// Safe
 	/// Depending on the build flag, a protected version of ManuallyDrop or
@@ -117,7 +120,11 @@ cfg_if_safemode! {
 	/// ```text
 	/// SafeManuallyDrop
 	/// ```
-	#if_safe(pub type AutoSafeManuallyDrop<T, Trig> = crate::beh::safe::SafeManuallyDrop<T, Trig>;)
+	#if_safe(pub type AutoSafeManuallyDrop<T, Trig, S = AtomicU8> = crate::beh::safe::SafeManuallyDrop<T, Trig, S>;)
 }
 
+/// Depending on the build flag, a protected version of ManuallyDrop or an unprotected version
+/// of ManuallyDrop, the state is stored in `Cell<u8>` (without atomic operations, ManuallyDrop is `!Sync`).
+pub type AutoSafeCellManuallyDrop<T, Trig> = AutoSafeManuallyDrop<T, Trig, Cell<u8>>;
+
 pub(crate) use cfg_if_safemode;
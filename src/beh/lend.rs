@@ -0,0 +1,125 @@
+//! Temporary lending of the value of ManuallyDrop (`lend`, `with_taken`): the value is
+//! moved out, the slot is marked as lent (any access to it before the value is put back
+//! executes the trigger), the value is put back when the guard is dropped, including
+//! during unwinding.
+//!
+//! ```rust
+//! use SafeManuallyDrop::AlwaysSafeEmptyLoopManuallyDrop as ManuallyDrop;
+//!
+//! let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+//!
+//! {
+//! 	let mut lent = ManuallyDrop::lend(&mut data);
+//! 	lent.push(4);
+//! } // <-- the value is put back
+//!
+//! let len = ManuallyDrop::with_taken(&mut data, |mut value| {
+//! 	value.push(5);
+//! 	let len = value.len();
+//!
+//! 	(value, len)
+//! });
+//! assert_eq!(len, 5);
+//! assert_eq!(*data, [1, 2, 3, 4, 5]);
+//!
+//! #[allow(unused_unsafe)]
+//! unsafe {
+//! 	ManuallyDrop::drop(&mut data);
+//! }
+//! ```
+
+use crate::core::state::StateManuallyDrop;
+use crate::core::state::StateStorage;
+use crate::UnsafeStdManuallyDrop;
+use core::fmt::Debug;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::sync::atomic::AtomicU8;
+
+/// The value temporarily moved out of ManuallyDrop, the value is put back
+/// to ManuallyDrop when the guard is dropped (see `lend`).
+pub struct LendGuard<'a, T, S: StateStorage = AtomicU8> {
+	slot: &'a mut UnsafeStdManuallyDrop<T>,
+	/// The state of the slot (None for the unsafe ManuallyDrop, it has no state).
+	state: Option<&'a StateManuallyDrop<S>>,
+	value: UnsafeStdManuallyDrop<T>,
+}
+
+impl<'a, T, S: StateStorage> LendGuard<'a, T, S> {
+	/// The value was moved out of `slot`, the state of the slot is already lent.
+	#[inline]
+	pub(crate) fn __new(
+		slot: &'a mut UnsafeStdManuallyDrop<T>,
+		state: Option<&'a StateManuallyDrop<S>>,
+		value: T,
+	) -> Self {
+		Self {
+			slot,
+			state,
+			value: UnsafeStdManuallyDrop::new(value),
+		}
+	}
+}
+
+impl<'a, T, S: StateStorage> Deref for LendGuard<'a, T, S> {
+	type Target = T;
+
+	#[inline(always)]
+	fn deref(&self) -> &T {
+		&self.value
+	}
+}
+
+impl<'a, T, S: StateStorage> DerefMut for LendGuard<'a, T, S> {
+	#[inline(always)]
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.value
+	}
+}
+
+impl<'a, T: Debug, S: StateStorage> Debug for LendGuard<'a, T, S> {
+	#[inline]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		f.debug_tuple("LendGuard").field(&*self.value).finish()
+	}
+}
+
+impl<'a, T, S: StateStorage> Drop for LendGuard<'a, T, S> {
+	#[inline]
+	fn drop(&mut self) {
+		// the value of the slot was moved out, nothing to drop
+		*self.slot = UnsafeStdManuallyDrop::new(unsafe {
+			// the value is taken once, the guard is being dropped
+			UnsafeStdManuallyDrop::take(&mut self.value)
+		});
+		if let Some(state) = self.state {
+			state.__return_lent();
+		}
+	}
+}
+
+/// Marks the lent value as taken if the function that took it panicked
+/// (the value was moved into the function and cannot be put back), see `with_taken`.
+pub(crate) struct LentPanicGuard<'a, S: StateStorage> {
+	state: &'a StateManuallyDrop<S>,
+}
+
+impl<'a, S: StateStorage> LentPanicGuard<'a, S> {
+	#[inline]
+	pub(crate) fn __new(state: &'a StateManuallyDrop<S>) -> Self {
+		Self { state }
+	}
+
+	/// The function returned the value, the guard is no longer needed.
+	#[inline]
+	pub(crate) fn disarm(self) {
+		core::mem::forget(self);
+	}
+}
+
+impl<'a, S: StateStorage> Drop for LentPanicGuard<'a, S> {
+	#[inline]
+	fn drop(&mut self) {
+		self.state.__lost_lent();
+	}
+}
@@ -3,21 +3,31 @@
 use crate::core::trig::TrigManuallyDrop;
 use crate::macro_codegen::__codegen;
 use crate::UnsafeStdManuallyDrop;
+use core::cell::Cell;
 use core::marker::PhantomData;
+use core::sync::atomic::AtomicU8;
 
 /// A safe version of the insecure manual control of freeing memory.
 // #[repr(transparent)]
-pub struct SafeManuallyDrop<T, Trig>
+///
+/// The state is stored in `S`: `AtomicU8` by default, or `Cell<u8>` for ManuallyDrop that is not
+/// shared between threads (cheaper checks, see `CellSafeManuallyDrop`).
+pub struct SafeManuallyDrop<T, Trig, S = AtomicU8>
 where
 	T: ?Sized,
 	Trig: TrigManuallyDrop,
+	S: StateStorage,
 {
-	state: StateManuallyDrop,
+	pub(crate) state: StateManuallyDrop<S>,
 	_pp: PhantomData<Trig>,
 
 	value: UnsafeStdManuallyDrop<T>,
 }
 
+/// A safe version of ManuallyDrop whose state is stored in `Cell<u8>`: without atomic
+/// operations, every check is cheaper, ManuallyDrop is `!Sync`.
+pub type CellSafeManuallyDrop<T, Trig> = SafeManuallyDrop<T, Trig, Cell<u8>>;
+
 __codegen! {
 	@use;
 	@impl SafeManuallyDrop {
@@ -31,7 +41,7 @@ __codegen! {
 			new |value| {
 				Self {
 					value,
-					state: StateManuallyDrop::EMPTY_STATE,
+					state: StateManuallyDrop::__empty(),
 					_pp: PhantomData
 				}
 			}
@@ -58,19 +68,21 @@ __codegen! {
 }
 
 //impl<T> Copy for ManuallyDrop<T> where T: ?Sized + Copy {} TODO
-impl<T, Trig> Drop for SafeManuallyDrop<T, Trig>
+impl<T, Trig, S> Drop for SafeManuallyDrop<T, Trig, S>
 where
 	T: ?Sized,
 	Trig: TrigManuallyDrop,
+	S: StateStorage,
 {
 	#[inline]
 	fn drop(&mut self) {
-		self.state.if_empty_then_run_trigfn::<Trig, _>(
-			"expected ManuallyDrop::drop(&mut value)",
-			|| unsafe {
+		let instance = self as *const Self as *const ();
+
+		self.state
+			.if_empty_then_run_trigfn::<Trig, T, _>(instance, || unsafe {
 				// What for? - >> to ignore miri errors allocate.
 				UnsafeStdManuallyDrop::drop(&mut self.value);
-			},
-		);
+			});
+		self.__unpoison_released();
 	}
 }
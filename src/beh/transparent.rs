@@ -0,0 +1,647 @@
+//! A safe version of ManuallyDrop with exactly the layout of `core::mem::ManuallyDrop<T>`
+//! (`repr(transparent)`), the state is stored in a global side table by the address of the value.
+//!
+//! Switching between `UnsafeManuallyDrop` and this type does not change the layout of
+//! `repr(C)` structures, so the checks can be enabled for types shared with FFI.
+//!
+//! Limitations (the state follows the address, not the value):
+//! - a released value must not be moved before it is dropped, the state stays
+//!   at the old address (the next release at the new address is not detected);
+//! - the state is forgotten only when ManuallyDrop is dropped in place, a released
+//!   ManuallyDrop that is moved, `mem::forget`-ed or whose memory is freed without
+//!   dropping it (by FFI code) leaves a stale state at its address, and a new ManuallyDrop
+//!   of the same type later placed at this address inherits it (false violations).
+//!   The table has no generations, call `forget_state` before the ManuallyDrop leaves
+//!   its address in such a way;
+//! - an implicit drop (the value was not released) is not detected and the value
+//!   is never dropped implicitly, exactly like std ManuallyDrop;
+//! - zero-sized values and `into_inner` (the value is moved) are not checked.
+//!
+//! ```rust
+//! use SafeManuallyDrop::beh::transparent::TransparentSafeManuallyDrop;
+//! use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+//! use SafeManuallyDrop::UnsafeStdManuallyDrop;
+//!
+//! type ManuallyDrop<T> = TransparentSafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+//!
+//! #[repr(C)]
+//! struct FfiBuffer {
+//! 	len: usize,
+//! 	data: ManuallyDrop<Vec<u8>>,
+//! }
+//! assert_eq!(
+//! 	core::mem::size_of::<ManuallyDrop<Vec<u8>>>(),
+//! 	core::mem::size_of::<UnsafeStdManuallyDrop<Vec<u8>>>()
+//! );
+//!
+//! let mut buffer = FfiBuffer {
+//! 	len: 3,
+//! 	data: ManuallyDrop::new(vec![1, 2, 3]),
+//! };
+//! assert_eq!(buffer.data.len(), buffer.len);
+//!
+//! unsafe {
+//! 	ManuallyDrop::drop(&mut buffer.data);
+//! 	// ManuallyDrop::drop(&mut buffer.data); <-- double drop is detected
+//! }
+//! ```
+
+use crate::core::report::ManuallyDropOperation;
+use crate::core::state::StateManuallyDrop;
+use crate::core::state::StateManuallyDropData;
+use crate::core::trig::TrigManuallyDrop;
+use crate::UnsafeStdManuallyDrop;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+/// The key of the side table: the address of ManuallyDrop and the name of the type of the value
+/// (nested ManuallyDrop can have the same address).
+type SideTableKey = (usize, &'static str);
+
+/// The states of the released ManuallyDrop, by address.
+static SIDE_TABLE: RwLock<BTreeMap<SideTableKey, Arc<StateManuallyDrop>>> =
+	RwLock::new(BTreeMap::new());
+
+/// The state of ManuallyDrop<T> located at `instance` (None if the value was not released).
+#[inline]
+fn find_side_state<T: ?Sized>(instance: *const ()) -> Option<Arc<StateManuallyDrop>> {
+	let table = match SIDE_TABLE.read() {
+		Ok(a) => a,
+		Err(e) => e.into_inner(),
+	};
+
+	table
+		.get(&(instance as usize, core::any::type_name::<T>()))
+		.cloned()
+}
+
+/// The state of ManuallyDrop<T> located at `instance`, an empty state is created if necessary.
+/// (The lock is not held while the trigger is executed.)
+#[inline]
+fn side_state<T: ?Sized>(instance: *const ()) -> Arc<StateManuallyDrop> {
+	if let Some(state) = find_side_state::<T>(instance) {
+		return state;
+	}
+
+	let mut table = match SIDE_TABLE.write() {
+		Ok(a) => a,
+		Err(e) => e.into_inner(),
+	};
+	table
+		.entry((instance as usize, core::any::type_name::<T>()))
+		.or_default()
+		.clone()
+}
+
+/// Forget the state of ManuallyDrop<T> located at `instance` (ManuallyDrop is dropped).
+/// (The write lock is taken only if there is a state, most values are never released.)
+#[inline]
+fn remove_side_state<T: ?Sized>(instance: *const ()) {
+	let key = (instance as usize, core::any::type_name::<T>());
+	let is_known = match SIDE_TABLE.read() {
+		Ok(a) => a.contains_key(&key),
+		Err(e) => e.into_inner().contains_key(&key),
+	};
+	if !is_known {
+		return;
+	}
+
+	let mut table = match SIDE_TABLE.write() {
+		Ok(a) => a,
+		Err(e) => e.into_inner(),
+	};
+	table.remove(&key);
+}
+
+/// A safe version of ManuallyDrop with exactly the layout of `core::mem::ManuallyDrop<T>`,
+/// the state is stored in a global side table by the address of the value
+/// (see the module documentation for the limitations).
+#[repr(transparent)]
+pub struct TransparentSafeManuallyDrop<T, Trig>
+where
+	T: ?Sized,
+	Trig: TrigManuallyDrop,
+{
+	_pp: PhantomData<Trig>,
+	value: UnsafeStdManuallyDrop<T>,
+}
+
+impl<T, Trig> TransparentSafeManuallyDrop<T, Trig>
+where
+	Trig: TrigManuallyDrop,
+{
+	/// Wrap a value to be manually dropped.
+	#[inline]
+	pub const fn new(value: T) -> Self {
+		Self {
+			_pp: PhantomData,
+			value: UnsafeStdManuallyDrop::new(value),
+		}
+	}
+
+	/// Wrap a value to be manually dropped.
+	/// Unsafe because the UnsafeStdManuallyDrop input argument is in an undefined state.
+	///
+	/// # Safety
+	///
+	/// The value in `UnsafeStdManuallyDrop` must not have been released (dropped or taken).
+	#[inline]
+	pub const unsafe fn from_std(value: UnsafeStdManuallyDrop<T>) -> Self {
+		Self {
+			_pp: PhantomData,
+			value,
+		}
+	}
+
+	/// Extracts the value from the ManuallyDrop container
+	/// (not checked, the value is moved).
+	#[inline]
+	pub fn into_inner(slot: TransparentSafeManuallyDrop<T, Trig>) -> T {
+		let slot = UnsafeStdManuallyDrop::new(slot);
+
+		// safe, the container is not used after the value is read
+		// and its destructor is not executed.
+		UnsafeStdManuallyDrop::into_inner(unsafe { core::ptr::read(&slot.value) })
+	}
+
+	/// Takes the value from the ManuallyDrop<T> container out.
+	///
+	/// # Safety
+	///
+	/// The value must not be used after it was taken (as `core::mem::ManuallyDrop::take`), the state is checked and the trigger is executed.
+	#[track_caller]
+	#[inline]
+	pub unsafe fn take(slot: &mut TransparentSafeManuallyDrop<T, Trig>) -> T {
+		if let Some(state) = slot.__release_state() {
+			if !state.to_takemode_or_trig::<Trig, T>(slot.__instance()) {
+				state.__refuse_operation(ManuallyDropOperation::Take);
+			}
+		}
+
+		UnsafeStdManuallyDrop::take(&mut slot.value)
+	}
+
+	/// Writes a new value to the released ManuallyDrop (after drop, take or ignore_drop)
+	/// and resets it to the initial state, so that the slot can be used again.
+	///
+	/// The trigger is executed if the value has not been released (the old value would
+	/// be leaked), if the trigger returns, the new value is dropped and the old value is kept.
+	#[track_caller]
+	#[inline]
+	pub fn reinit(slot: &mut TransparentSafeManuallyDrop<T, Trig>, value: T) {
+		if let Some(state) = slot.__release_state() {
+			if !state.reinit_or_trig::<Trig, T>(slot.__instance()) {
+				return;
+			}
+
+			remove_side_state::<T>(slot.__instance());
+		}
+
+		slot.value = UnsafeStdManuallyDrop::new(value);
+	}
+
+	/// Replaces the value of ManuallyDrop with a new one, returns the old value
+	/// (the state does not change).
+	///
+	/// The trigger is executed if the value has already been released.
+	#[track_caller]
+	#[inline]
+	pub fn replace(slot: &mut TransparentSafeManuallyDrop<T, Trig>, value: T) -> T {
+		if slot.__is_checked() {
+			if let Some(state) = find_side_state::<T>(slot.__instance()) {
+				if !state
+					.check_or_trig::<Trig, T>(slot.__instance(), ManuallyDropOperation::Replace)
+				{
+					state.__refuse_operation(ManuallyDropOperation::Replace);
+				}
+			}
+		}
+
+		core::mem::replace(&mut *slot.value, value)
+	}
+
+	/// Returns the value of ManuallyDrop, if the value has been released (after drop,
+	/// take or ignore_drop), a new value is created by `f` and written (see `reinit`).
+	///
+	/// The trigger is executed if the value is being dropped or if the state is corrupted
+	/// (it is unknown whether the value was released), as in `SafeManuallyDrop::get_or_reinit`.
+	#[track_caller]
+	#[inline]
+	pub fn get_or_reinit(
+		slot: &mut TransparentSafeManuallyDrop<T, Trig>,
+		f: impl FnOnce() -> T,
+	) -> &mut T {
+		if slot.__is_checked() {
+			if let Some(state) = find_side_state::<T>(slot.__instance()) {
+				if !state.read().is_empty() {
+					if !state.reinit_or_trig::<Trig, T>(slot.__instance()) {
+						// the value is being dropped or may not have been released
+						state.__refuse_operation(ManuallyDropOperation::Reinit);
+					}
+
+					slot.value = UnsafeStdManuallyDrop::new(f());
+					remove_side_state::<T>(slot.__instance());
+				}
+			}
+		}
+
+		&mut slot.value
+	}
+}
+
+impl<T, Trig> TransparentSafeManuallyDrop<T, Trig>
+where
+	T: ?Sized,
+	Trig: TrigManuallyDrop,
+{
+	/// Address of the ManuallyDrop, the key of its state.
+	#[inline(always)]
+	fn __instance(&self) -> *const () {
+		self as *const Self as *const ()
+	}
+
+	/// Zero-sized values are not checked (many values can have the same address).
+	#[inline(always)]
+	fn __is_checked(&self) -> bool {
+		core::mem::size_of_val::<T>(&self.value) != 0
+	}
+
+	/// The state of the value for the release (None if the value is not checked).
+	#[inline]
+	fn __release_state(&self) -> Option<Arc<StateManuallyDrop>> {
+		if !self.__is_checked() {
+			return None;
+		}
+
+		Some(side_state::<T>(self.__instance()))
+	}
+
+	/// Manually drops the contained value.
+	///
+	/// # Safety
+	///
+	/// The value must not be used after it was dropped (as `core::mem::ManuallyDrop::drop`), the state is checked and the trigger is executed.
+	#[track_caller]
+	#[inline]
+	pub unsafe fn drop(slot: &mut TransparentSafeManuallyDrop<T, Trig>) {
+		if let Some(state) = slot.__release_state() {
+			if !state.to_indropmode_or_trig::<Trig, T>(slot.__instance()) {
+				// the trigger returned, the value has already been released (recovery mode)
+				return;
+			}
+
+			// re-entrancy and a panic of the destructor are tracked by the state
+			return state.__in_drop(|| UnsafeStdManuallyDrop::drop(&mut slot.value));
+		}
+
+		UnsafeStdManuallyDrop::drop(&mut slot.value)
+	}
+
+	/// Note that the value must not be used after ignore_drop.
+	/// The version of mem::forget is adapted for ManuallyDrop.
+	///
+	/// # Safety
+	///
+	/// The value must not be used after ignore_drop.
+	#[track_caller]
+	#[inline]
+	pub unsafe fn ignore_drop(&self) {
+		if let Some(state) = self.__release_state() {
+			state.to_ignore_trig_when_drop::<Trig, T>(self.__instance());
+		}
+	}
+
+	/// Checks that the value can be read, refuses the operation if the trigger returned.
+	#[track_caller]
+	#[inline]
+	fn __check_deref(&self) {
+		if !self.__is_checked() {
+			return;
+		}
+
+		if let Some(state) = find_side_state::<T>(self.__instance()) {
+			if !state.deref_or_trig::<Trig, T>(self.__instance()) {
+				state.__refuse_operation(ManuallyDropOperation::Deref);
+			}
+		}
+	}
+
+	/// Get reference to value.
+	#[track_caller]
+	#[inline]
+	pub fn as_value(&self) -> &T {
+		self.__check_deref();
+
+		&self.value
+	}
+
+	/// Get a mutable reference to a value.
+	#[track_caller]
+	#[inline]
+	pub fn as_mut_value(&mut self) -> &mut T {
+		self.__check_deref();
+
+		&mut self.value
+	}
+
+	/// Forget the state of ManuallyDrop (it becomes empty), call it before a released
+	/// ManuallyDrop leaves its address without being dropped (moved, `mem::forget`,
+	/// memory freed by FFI code), otherwise its stale state is inherited by the next
+	/// ManuallyDrop at this address.
+	///
+	/// # Safety
+	///
+	/// After the state is forgotten, a released value is no longer detected, the value
+	/// must not be accessed again until it is reinitialized (see `reinit`).
+	#[inline]
+	pub unsafe fn forget_state(slot: &TransparentSafeManuallyDrop<T, Trig>) {
+		if slot.__is_checked() {
+			remove_side_state::<T>(slot.__instance());
+		}
+	}
+
+	/// Get current state (from the side table).
+	#[inline]
+	pub fn get_state(&self) -> Option<StateManuallyDropData> {
+		match find_side_state::<T>(self.__instance()) {
+			Some(state) => Some(state.read()),
+			None => Some(StateManuallyDropData::Empty),
+		}
+	}
+
+	/// Get reference to value. Always unprotected!
+	///
+	/// # Safety
+	///
+	/// The value must not have been released (drop, take), the access is never checked.
+	#[inline(always)]
+	pub unsafe fn force_as_value(&self) -> &T {
+		&self.value
+	}
+
+	/// Get a mutable reference to a value. Always unprotected!
+	///
+	/// # Safety
+	///
+	/// The value must not have been released (drop, take), the access is never checked.
+	#[inline(always)]
+	pub unsafe fn force_as_mut_value(&mut self) -> &mut T {
+		&mut self.value
+	}
+
+	/// Safe or insecure version of ManuallyDrop.
+	#[inline(always)]
+	pub const fn is_safe_type(&self) -> bool {
+		true
+	}
+
+	/// Is ManuallyDrop a wrapper with values, or is it actually a transparent
+	/// value with no false data.
+	#[inline(always)]
+	pub const fn is_repr_transparent(&self) -> bool {
+		true
+	}
+}
+
+impl<T, Trig> Drop for TransparentSafeManuallyDrop<T, Trig>
+where
+	T: ?Sized,
+	Trig: TrigManuallyDrop,
+{
+	/// The state is forgotten, the value is never dropped implicitly.
+	#[inline]
+	fn drop(&mut self) {
+		if self.__is_checked() {
+			remove_side_state::<T>(self.__instance());
+		}
+	}
+}
+
+impl<T, Trig> Deref for TransparentSafeManuallyDrop<T, Trig>
+where
+	T: ?Sized,
+	Trig: TrigManuallyDrop,
+{
+	type Target = T;
+
+	#[track_caller]
+	#[inline(always)]
+	fn deref(&self) -> &T {
+		self.as_value()
+	}
+}
+
+impl<T, Trig> DerefMut for TransparentSafeManuallyDrop<T, Trig>
+where
+	T: ?Sized,
+	Trig: TrigManuallyDrop,
+{
+	#[track_caller]
+	#[inline(always)]
+	fn deref_mut(&mut self) -> &mut T {
+		self.as_mut_value()
+	}
+}
+
+impl<T, Trig> Clone for TransparentSafeManuallyDrop<T, Trig>
+where
+	T: Clone,
+	Trig: TrigManuallyDrop,
+{
+	#[track_caller]
+	#[inline]
+	fn clone(&self) -> Self {
+		Self::new(Clone::clone(self as &T))
+	}
+}
+
+impl<T, Trig> Default for TransparentSafeManuallyDrop<T, Trig>
+where
+	T: Default,
+	Trig: TrigManuallyDrop,
+{
+	#[inline(always)]
+	fn default() -> Self {
+		Self::new(Default::default())
+	}
+}
+
+impl<T, Trig> From<T> for TransparentSafeManuallyDrop<T, Trig>
+where
+	Trig: TrigManuallyDrop,
+{
+	#[inline(always)]
+	fn from(a: T) -> Self {
+		Self::new(a)
+	}
+}
+
+impl<T, Trig> Debug for TransparentSafeManuallyDrop<T, Trig>
+where
+	T: ?Sized + Debug,
+	Trig: TrigManuallyDrop,
+{
+	#[inline]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		Debug::fmt(self as &T, f)
+	}
+}
+
+crate::beh::auto::cfg_if_safemode! {
+	// Unsafe
+	/// Depending on the build flag, a protected version of ManuallyDrop with the layout of
+	/// `core::mem::ManuallyDrop<T>` (side table) or an unprotected version of ManuallyDrop.
+	#if_not_safe(pub type AutoSafeTransparentManuallyDrop<T, Trig> = crate::beh::r#unsafe::UnsafeManuallyDrop<T, Trig>;)
+
+	// Safe
+	/// Depending on the build flag, a protected version of ManuallyDrop with the layout of
+	/// `core::mem::ManuallyDrop<T>` (side table) or an unprotected version of ManuallyDrop.
+	#if_safe(pub type AutoSafeTransparentManuallyDrop<T, Trig> = TransparentSafeManuallyDrop<T, Trig>;)
+}
+
+#[cfg(test)]
+#[test]
+#[allow(unused_unsafe)]
+fn test_transparent_side_table() {
+	use crate::core::report::ViolationKind;
+	use core::fmt::Arguments;
+	use core::sync::atomic::AtomicUsize;
+	use core::sync::atomic::Ordering;
+
+	static TRIGS: AtomicUsize = AtomicUsize::new(0);
+
+	enum CountTrig {}
+	impl TrigManuallyDrop for CountTrig {
+		type Output = ();
+
+		fn trig_next_invalid_beh(_a: Arguments<'_>) {
+			TRIGS.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+	type ManuallyDrop<T> = TransparentSafeManuallyDrop<T, CountTrig>;
+
+	assert_eq!(
+		core::mem::size_of::<ManuallyDrop<[u64; 3]>>(),
+		core::mem::size_of::<UnsafeStdManuallyDrop<[u64; 3]>>()
+	);
+	assert_eq!(
+		core::mem::align_of::<ManuallyDrop<u16>>(),
+		core::mem::align_of::<UnsafeStdManuallyDrop<u16>>()
+	);
+
+	let address = {
+		let mut a = ManuallyDrop::new([1, 2, 3]);
+		assert_eq!(a.get_state(), Some(StateManuallyDropData::Empty));
+		assert_eq!(a.len(), 3);
+		unsafe {
+			ManuallyDrop::drop(&mut a);
+		}
+		assert_eq!(a.get_state(), Some(StateManuallyDropData::DropModeTrig));
+		assert_eq!(TRIGS.load(Ordering::SeqCst), 0);
+
+		unsafe {
+			ManuallyDrop::drop(&mut a); // double drop, skipped
+		}
+		assert_eq!(TRIGS.load(Ordering::SeqCst), 1);
+
+		let report = crate::core::testing::expect_violation(|| {
+			let _len = a.len();
+		});
+		assert_eq!(report.kind(), ViolationKind::UseAfterDrop);
+		assert_eq!(TRIGS.load(Ordering::SeqCst), 1);
+
+		&a as *const ManuallyDrop<[i32; 3]> as *const ()
+	}; // <-- the state is forgotten with ManuallyDrop (dropped in place)
+	assert!(find_side_state::<[i32; 3]>(address).is_none());
+
+	let mut b = ManuallyDrop::new(vec![1, 2, 3]);
+	assert_eq!(unsafe { ManuallyDrop::take(&mut b) }, [1, 2, 3]);
+	assert_eq!(b.get_state(), Some(StateManuallyDropData::TakeModeTrig));
+	ManuallyDrop::reinit(&mut b, vec![4]);
+	assert_eq!(b.get_state(), Some(StateManuallyDropData::Empty));
+	assert_eq!(ManuallyDrop::replace(&mut b, vec![5]), [4]);
+	unsafe {
+		ManuallyDrop::drop(&mut b);
+	}
+	assert_eq!(ManuallyDrop::get_or_reinit(&mut b, || vec![6]), &[6]);
+	assert_eq!(unsafe { ManuallyDrop::take(&mut b) }, [6]);
+	assert_eq!(ManuallyDrop::into_inner(ManuallyDrop::new(4)), 4);
+
+	// a released ManuallyDrop that leaves its address without being dropped
+	let mut slot = core::mem::MaybeUninit::<ManuallyDrop<[u8; 4]>>::uninit();
+	let c = slot.write(ManuallyDrop::new([1, 2, 3, 4]));
+	unsafe {
+		ManuallyDrop::drop(c);
+	}
+	let c = slot.write(ManuallyDrop::new([5, 6, 7, 8])); // <-- the old one is not dropped
+	assert_eq!(c.get_state(), Some(StateManuallyDropData::DropModeTrig)); // stale state
+	unsafe {
+		// the new value was written, the released one is not accessed again
+		ManuallyDrop::forget_state(c);
+	}
+	assert_eq!(c.get_state(), Some(StateManuallyDropData::Empty));
+	assert_eq!(c.len(), 4);
+	assert_eq!(TRIGS.load(Ordering::SeqCst), 1);
+
+	// without forget_state, the released ManuallyDrop is still detected
+	let mut d = ManuallyDrop::new([1u8; 4]);
+	unsafe {
+		ManuallyDrop::drop(&mut d);
+	}
+	let report = crate::core::testing::expect_violation(|| {
+		let _len = d.len();
+	});
+	assert_eq!(report.kind(), ViolationKind::UseAfterDrop);
+	unsafe {
+		ManuallyDrop::drop(&mut d); // double drop, skipped
+	}
+	assert_eq!(TRIGS.load(Ordering::SeqCst), 2);
+	assert_eq!(ManuallyDrop::get_or_reinit(&mut d, || [2; 4]), &[2; 4]);
+	assert_eq!(d.get_state(), Some(StateManuallyDropData::Empty));
+
+	// the value being dropped is not reinitialized
+	struct ReinitInDrop(*mut ManuallyDrop<ReinitInDrop>);
+	impl Drop for ReinitInDrop {
+		fn drop(&mut self) {
+			let slot = unsafe { &mut *self.0 };
+			let report = crate::core::testing::expect_violation(|| {
+				ManuallyDrop::get_or_reinit(slot, || ReinitInDrop(core::ptr::null_mut()));
+			});
+			assert_eq!(report.kind(), ViolationKind::UseDuringDrop);
+		}
+	}
+	let mut e = ManuallyDrop::new(ReinitInDrop(core::ptr::null_mut()));
+	e.0 = &mut e;
+	unsafe {
+		ManuallyDrop::drop(&mut e);
+	}
+	assert_eq!(e.get_state(), Some(StateManuallyDropData::DropModeTrig));
+}
+
+#[cfg(test)]
+#[test]
+#[allow(unused_unsafe)]
+fn test_auto_transparent_api() {
+	use crate::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+
+	// the same code compiles with the safe and the unsafe version
+	type ManuallyDrop<T> = AutoSafeTransparentManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+	let mut a = ManuallyDrop::new(vec![1, 2]);
+	assert_eq!(
+		unsafe { ManuallyDrop::get_or_reinit(&mut a, || vec![3]) },
+		&[1, 2]
+	);
+	unsafe {
+		ManuallyDrop::forget_state(&a);
+	}
+	unsafe {
+		ManuallyDrop::drop(&mut a);
+	}
+	assert!(a.is_repr_transparent());
+}
@@ -4,15 +4,20 @@ use crate::core::trig::TrigManuallyDrop;
 use crate::macro_codegen::__codegen;
 use crate::UnsafeStdManuallyDrop;
 use core::marker::PhantomData;
+use core::sync::atomic::AtomicU8;
 
 /// Insecure standard implementation of manual memory management.
+///
+/// (`S` is not used, it is needed for compatibility with SafeManuallyDrop,
+/// the type is `!Sync` with the same `S` as SafeManuallyDrop.)
 #[repr(transparent)]
-pub struct UnsafeManuallyDrop<T, Trig>
+pub struct UnsafeManuallyDrop<T, Trig, S = AtomicU8>
 where
 	T: ?Sized,
 	Trig: TrigManuallyDrop,
+	S: StateStorage,
 {
-	_pp: PhantomData<Trig>,
+	_pp: PhantomData<(Trig, S)>,
 	value: UnsafeStdManuallyDrop<T>,
 }
 
@@ -54,9 +59,10 @@ __codegen! {
 	}
 }
 
-impl<T, Trig> Copy for UnsafeManuallyDrop<T, Trig>
+impl<T, Trig, S> Copy for UnsafeManuallyDrop<T, Trig, S>
 where
-	T: ?Sized + Copy,
+	T: Copy,
 	Trig: TrigManuallyDrop,
+	S: StateStorage,
 {
 }
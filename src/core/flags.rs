@@ -60,6 +60,58 @@ pub const BUILD_FLAG_COUNT_TRIGGER_ENABLED: bool = {
 	}
 };
 
+/// Whether the library was built with support for the alloc library.
+pub const BUILD_FLAG_ALLOC_ENABLED: bool = {
+	#[cfg(feature = "alloc")]
+	{
+		true
+	}
+
+	#[cfg(not(feature = "alloc"))]
+	{
+		false
+	}
+};
+
+/// Whether the library was built with support for the standard library.
+pub const BUILD_FLAG_STD_ENABLED: bool = {
+	#[cfg(feature = "std")]
+	{
+		true
+	}
+
+	#[cfg(not(feature = "std"))]
+	{
+		false
+	}
+};
+
+/// Whether the location of the first release is remembered.
+pub const BUILD_FLAG_TRACK_LOCATION_ENABLED: bool = {
+	#[cfg(feature = "track_location")]
+	{
+		true
+	}
+
+	#[cfg(not(feature = "track_location"))]
+	{
+		false
+	}
+};
+
+/// Whether backtraces of the first release and of the violation are captured.
+pub const BUILD_FLAG_BACKTRACE_ENABLED: bool = {
+	#[cfg(feature = "backtrace")]
+	{
+		true
+	}
+
+	#[cfg(not(feature = "backtrace"))]
+	{
+		false
+	}
+};
+
 /// Whether the library build flag was used to support loop_trig.
 pub const BUILD_FLAG_LOOP_TRIGGER_ENABLED: bool = true;
 
@@ -108,7 +160,9 @@ fn test_flag_is_safe_mode() {
 
 	#[cfg(feature = "always_safe_manuallydrop")]
 	{
-		assert_eq!(SAFE_MANUALLYDROP_ENABLED, true);
+		// clippy::assertions_on_constants why? it's part of this test, it's okay.
+		#![allow(clippy::assertions_on_constants)]
+		assert!(SAFE_MANUALLYDROP_ENABLED);
 
 		//#[allow(unused_assignments)] // error[E0658]: attributes on expressions are experimental
 		is_checked_c = 1;
@@ -129,9 +183,11 @@ fn test_flag_is_safe_mode() {
 		feature = "always_safe_manuallydrop"
 	)))]
 	{
-		assert_eq!(SAFE_MANUALLYDROP_ENABLED, false);
+		// clippy::assertions_on_constants why? it's part of this test, it's okay.
+		#![allow(clippy::assertions_on_constants)]
+		assert!(!SAFE_MANUALLYDROP_ENABLED);
 
-		is_checked_c = is_checked_c + 1;
+		is_checked_c += 1;
 	}
 
 	assert_eq!(is_checked_c, 1);
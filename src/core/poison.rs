@@ -0,0 +1,121 @@
+//! Poisoning of the memory of the released values of the safe ManuallyDrop, for the types
+//! that opt in by wrapping their trigger in `WithPoisonFill`: after drop or take, the bytes
+//! of the value are filled with `POISON_FILL_BYTE` and, when the crate is built with
+//! AddressSanitizer (`-Zsanitizer=address`), poisoned with `__asan_poison_memory_region`.
+//! So a raw pointer obtained before the release (`as_ptr`, `as_mut_ptr`, `force_as_value`)
+//! reads an obvious pattern instead of stale but plausible data, or is reported by ASan.
+//! The bytes are unpoisoned when a new value is written (`reinit`, `get_state_and_reset`)
+//! and when ManuallyDrop is destroyed.
+//!
+//! ```rust
+//! # #[cfg(not(safemanuallydrop_asan))] {
+//! use SafeManuallyDrop::core::poison::{WithPoisonFill, POISON_FILL_BYTE};
+//! use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+//! use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+//!
+//! type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, WithPoisonFill<EmptyLoopTrigManuallyDrop>>;
+//!
+//! let mut data = Box::new(ManuallyDrop::new(0x12345678u32));
+//! let ptr = unsafe { data.as_ptr() };
+//!
+//! #[allow(unused_unsafe)]
+//! let _value = unsafe { ManuallyDrop::take(&mut data) };
+//! assert_eq!(unsafe { ptr.read() }, u32::from_ne_bytes([POISON_FILL_BYTE; 4]));
+//! # }
+//! ```
+//!
+//! Note that the filled bytes are usually not a valid value of the type (`bool`, `char`,
+//! enums, references, `NonNull`, `Box`, ...), while `ManuallyDrop<T>` must always contain
+//! a valid `T`, so the opt-in is a contract of the type, not a global switch:
+//! - a released ManuallyDrop of such a type must not be moved, swapped or copied
+//!   (`mem::swap`, `mem::replace`, returning it by value, `Vec` growth) until a new value is
+//!   written (`reinit`) or it is destroyed in place, a typed copy of an invalid value is
+//!   undefined behavior, and under AddressSanitizer the move reads the poisoned bytes and
+//!   is reported; keep it in place (for example, in a `Box`);
+//! - `force_as_value` of the released value is undefined behavior.
+//!
+//! The released values of the other types are left as they are and can be moved as usual.
+//! Values extracted with `into_inner` are not poisoned (the container is moved).
+
+use crate::core::report::ViolationReport;
+use crate::core::trig::policy::ImplicitDropPolicy;
+use crate::core::trig::TrigManuallyDrop;
+use crate::UnsafeStdManuallyDrop;
+#[cfg(safemanuallydrop_asan)]
+use core::ffi::c_void;
+use core::fmt::Arguments;
+use core::marker::PhantomData;
+use core::ptr;
+
+/// The byte with which the memory of the released value is filled.
+pub const POISON_FILL_BYTE: u8 = 0xDD;
+
+/// The trigger `Trig` of a type whose released values are poisoned (`POISON_RELEASED`),
+/// all violations are passed to `Trig`. The released ManuallyDrop of this type must
+/// not be moved until reinit (see the module documentation).
+pub struct WithPoisonFill<Trig>(PhantomData<Trig>);
+
+impl<Trig> TrigManuallyDrop for WithPoisonFill<Trig>
+where
+	Trig: TrigManuallyDrop,
+{
+	type Output = Trig::Output;
+
+	const IMPLICIT_DROP_POLICY: ImplicitDropPolicy = Trig::IMPLICIT_DROP_POLICY;
+
+	const POISON_RELEASED: bool = true;
+
+	#[inline(always)]
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> Trig::Output {
+		Trig::trig_next_invalid_beh(a)
+	}
+
+	#[inline(always)]
+	fn trig_violation(report: &ViolationReport) -> Trig::Output {
+		Trig::trig_violation(report)
+	}
+}
+
+// AddressSanitizer interface (the crate is built with `-Zsanitizer=address`,
+// the `safemanuallydrop_asan` cfg is set by the build script).
+#[cfg(safemanuallydrop_asan)]
+extern "C" {
+	fn __asan_poison_memory_region(addr: *const c_void, size: usize);
+	fn __asan_unpoison_memory_region(addr: *const c_void, size: usize);
+}
+
+/// The value was released (drop, take): its bytes are filled with `POISON_FILL_BYTE`
+/// and poisoned for AddressSanitizer.
+///
+/// # Safety
+///
+/// The value was released (dropped or moved out) and must not be used, the type opted in
+/// (`WithPoisonFill`): ManuallyDrop is not moved until a new value is written (the bytes may be invalid for `T`).
+#[inline]
+pub(crate) unsafe fn poison_released<T: ?Sized>(value: &mut UnsafeStdManuallyDrop<T>) {
+	// only the metadata of the pointer is read.
+	let size = core::mem::size_of_val(value);
+	let data = value as *mut UnsafeStdManuallyDrop<T> as *mut u8;
+
+	ptr::write_bytes(data, POISON_FILL_BYTE, size);
+
+	#[cfg(safemanuallydrop_asan)]
+	__asan_poison_memory_region(data as *const c_void, size);
+}
+
+/// A new value is written to the released ManuallyDrop or it is destroyed,
+/// its bytes are unpoisoned for AddressSanitizer.
+#[cfg(safemanuallydrop_asan)]
+#[inline]
+pub(crate) fn unpoison_released<T: ?Sized>(value: &UnsafeStdManuallyDrop<T>) {
+	// only the metadata of the pointer is read.
+	let size = core::mem::size_of_val(value);
+
+	unsafe {
+		// the bytes are not accessed, only the shadow memory of ASan is changed.
+		__asan_unpoison_memory_region(
+			value as *const UnsafeStdManuallyDrop<T> as *const c_void,
+			size,
+		);
+	}
+}
@@ -0,0 +1,308 @@
+//! Registry of live SafeManuallyDrop, report of leaked values.
+//!
+//! Each ManuallyDrop is registered when it is created (`new`) and unregistered when its
+//! value is released (drop, take, into_inner, ignore_drop) or when it is dropped, so the
+//! values that were leaked (`mem::forget`, `Box::leak`, cycles) remain in the registry and
+//! can be reported (`report_outstanding`, or at the exit of the process with
+//! `report_outstanding_at_exit`).
+//!
+//! ```rust
+//! use SafeManuallyDrop::core::registry::live_instances;
+//! use SafeManuallyDrop::AlwaysSafeEmptyLoopManuallyDrop as ManuallyDrop;
+//!
+//! let data = ManuallyDrop::new([1, 2, 3]);
+//! let line = line!() - 1;
+//! std::mem::forget(data); // <-- leak
+//!
+//! let leaked = live_instances()
+//! 	.into_iter()
+//! 	.find(|a| a.location().file() == file!() && a.location().line() == line)
+//! 	.unwrap();
+//! assert_eq!(leaked.type_name(), "[i32; 3]");
+//! ```
+
+use core::fmt::Display;
+use core::panic::Location;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::vec::Vec;
+
+/// Registry entry of a live ManuallyDrop, shared with its state.
+#[derive(Debug)]
+pub(crate) struct LiveEntry {
+	id: u64,
+	type_name: &'static str,
+	location: &'static Location<'static>,
+	/// The last address at which the ManuallyDrop was used (0 if it was not used).
+	address: AtomicUsize,
+}
+
+impl LiveEntry {
+	/// Register a new ManuallyDrop<T> created at `location`.
+	pub(crate) fn register<T: ?Sized>(location: &'static Location<'static>) -> Arc<LiveEntry> {
+		static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+		let entry = Arc::new(LiveEntry {
+			id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+			type_name: core::any::type_name::<T>(),
+			location,
+			address: AtomicUsize::new(0),
+		});
+		entry.reregister();
+
+		entry
+	}
+
+	/// Register the ManuallyDrop again (its value is live again, `get_and_reset`).
+	#[inline]
+	pub(crate) fn reregister(self: &Arc<Self>) {
+		registry().insert(self.id, self.clone());
+	}
+
+	/// Unregister the ManuallyDrop (its value was released or it was dropped).
+	#[inline]
+	pub(crate) fn unregister(&self) {
+		registry().remove(&self.id);
+	}
+
+	/// Remember the address at which the ManuallyDrop was used.
+	#[inline]
+	pub(crate) fn seen_at(&self, instance: *const ()) {
+		self.address.store(instance as usize, Ordering::Relaxed);
+	}
+}
+
+/// All live ManuallyDrop, by id.
+static REGISTRY: Mutex<BTreeMap<u64, Arc<LiveEntry>>> = Mutex::new(BTreeMap::new());
+
+#[inline]
+fn registry() -> MutexGuard<'static, BTreeMap<u64, Arc<LiveEntry>>> {
+	match REGISTRY.lock() {
+		Ok(a) => a,
+		Err(e) => e.into_inner(),
+	}
+}
+
+/// Description of a live ManuallyDrop (created but neither released nor dropped).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LiveInstance {
+	id: u64,
+	type_name: &'static str,
+	location: &'static Location<'static>,
+	address: Option<usize>,
+}
+
+impl LiveInstance {
+	/// Unique number of the ManuallyDrop, in the order of creation.
+	#[inline]
+	pub const fn id(&self) -> u64 {
+		self.id
+	}
+
+	/// Name of the type of the value in ManuallyDrop (`core::any::type_name`).
+	#[inline]
+	pub const fn type_name(&self) -> &'static str {
+		self.type_name
+	}
+
+	/// The place in the code where the ManuallyDrop was created.
+	#[inline]
+	pub const fn location(&self) -> &'static Location<'static> {
+		self.location
+	}
+
+	/// The last address at which the ManuallyDrop was used (ManuallyDrop can be moved,
+	/// so the address is known only after it was dereferenced).
+	#[inline]
+	pub const fn address(&self) -> Option<usize> {
+		self.address
+	}
+}
+
+impl From<&LiveEntry> for LiveInstance {
+	#[inline]
+	fn from(a: &LiveEntry) -> Self {
+		Self {
+			id: a.id,
+			type_name: a.type_name,
+			location: a.location,
+			address: match a.address.load(Ordering::Relaxed) {
+				0 => None,
+				address => Some(address),
+			},
+		}
+	}
+}
+
+impl Display for LiveInstance {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		write!(
+			f,
+			"ManuallyDrop<{}> #{} created at {}:{}",
+			self.type_name,
+			self.id,
+			self.location.file(),
+			self.location.line()
+		)?;
+		if let Some(address) = self.address {
+			write!(f, ", last used at {:#x}", address)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// All ManuallyDrop that were created and whose value was neither released nor
+/// ignore_dropped, and which were not dropped either, in the order of creation.
+pub fn live_instances() -> Vec<LiveInstance> {
+	registry()
+		.values()
+		.map(|a| LiveInstance::from(&**a))
+		.collect()
+}
+
+/// Print the live ManuallyDrop to stderr (nothing is printed if there are none).
+fn write_outstanding(instances: &[LiveInstance]) -> std::io::Result<()> {
+	if instances.is_empty() {
+		return Ok(());
+	}
+
+	let mut stderr = std::io::stderr().lock();
+	writeln!(
+		stderr,
+		"{} ManuallyDrop were neither released nor dropped:",
+		instances.len()
+	)?;
+	for a in instances.iter() {
+		writeln!(stderr, " - {}", a)?;
+	}
+
+	Ok(())
+}
+
+/// Print all live ManuallyDrop (see `live_instances`) to stderr,
+/// returns their number (nothing is printed if there are none).
+pub fn report_outstanding() -> usize {
+	let instances = live_instances();
+	let _e = write_outstanding(&instances);
+
+	instances.len()
+}
+
+/// Print a summary of the live ManuallyDrop (see `report_outstanding`) when the process exits
+/// normally (after `main` returns or on `std::process::exit`), the hook is registered with
+/// the C `atexit` once, repeated calls do nothing. Nothing is printed if the process is aborted.
+///
+/// ```rust
+/// fn main() {
+/// 	SafeManuallyDrop::core::registry::report_outstanding_at_exit();
+///
+/// 	// ...
+/// }
+/// ```
+#[cfg(any(unix, windows))]
+#[cfg_attr(docsrs, doc(cfg(any(unix, windows))))]
+pub fn report_outstanding_at_exit() {
+	extern "C" {
+		fn atexit(f: extern "C" fn()) -> core::ffi::c_int;
+	}
+
+	extern "C" fn report_at_exit() {
+		// a panic must not unwind into the C runtime
+		let _e = std::panic::catch_unwind(|| write_outstanding(&live_instances()));
+	}
+
+	static REGISTER: std::sync::Once = std::sync::Once::new();
+	REGISTER.call_once(|| unsafe {
+		// the hook is a plain function without arguments, it is registered once
+		atexit(report_at_exit);
+	});
+}
+
+#[cfg(test)]
+#[test]
+fn test_live_instances() {
+	use crate::AlwaysSafeEmptyLoopManuallyDrop;
+
+	let is_live = |line: u32| {
+		live_instances()
+			.iter()
+			.any(|a| a.location().file() == file!() && a.location().line() == line)
+	};
+
+	let mut a = AlwaysSafeEmptyLoopManuallyDrop::new([1, 2, 3]);
+	let line = line!() - 1;
+	assert!(is_live(line));
+
+	let _len = a.len();
+	let instance = live_instances()
+		.into_iter()
+		.find(|a| a.location().line() == line && a.location().file() == file!())
+		.unwrap();
+	assert_eq!(instance.type_name(), "[i32; 3]");
+	assert!(instance.address().is_some());
+
+	#[allow(unused_unsafe)]
+	unsafe {
+		AlwaysSafeEmptyLoopManuallyDrop::drop(&mut a);
+	}
+	assert!(!is_live(line));
+
+	// the value is live again
+	unsafe {
+		a.get_state_and_reset();
+	}
+	assert!(is_live(line));
+	#[allow(unused_unsafe)]
+	unsafe {
+		a.ignore_drop();
+	}
+	assert!(!is_live(line));
+
+	let b = AlwaysSafeEmptyLoopManuallyDrop::new([4, 5, 6]);
+	let line = line!() - 1;
+	core::mem::forget(b);
+	assert!(is_live(line));
+}
+
+#[cfg(all(test, any(unix, windows)))]
+#[test]
+fn test_report_outstanding_at_exit() {
+	use crate::AlwaysSafeEmptyLoopManuallyDrop;
+
+	// the summary is printed when the process exits, the test runs itself in a child process
+	const CHILD_ENV: &str = "SAFEMANUALLYDROP_TEST_AT_EXIT";
+	if std::env::var_os(CHILD_ENV).is_some() {
+		report_outstanding_at_exit();
+		report_outstanding_at_exit(); // <-- registered once
+		std::mem::forget(AlwaysSafeEmptyLoopManuallyDrop::new([7u8; 3]));
+
+		return;
+	}
+
+	let output = std::process::Command::new(std::env::current_exe().unwrap())
+		.args([
+			"--exact",
+			"core::registry::test_report_outstanding_at_exit",
+			"--test-threads=1",
+		])
+		.env(CHILD_ENV, "1")
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	assert_eq!(
+		stderr
+			.matches("ManuallyDrop were neither released nor dropped:")
+			.count(),
+		1
+	);
+	assert!(stderr.contains("ManuallyDrop<[u8; 3]>"));
+}
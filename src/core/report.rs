@@ -0,0 +1,556 @@
+//! Structured description of the undefined behavior detected by ManuallyDrop.
+
+use crate::core::state::StateManuallyDropData;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt::Arguments;
+use core::fmt::Display;
+use core::panic::Location;
+#[cfg(feature = "backtrace")]
+use std::backtrace::Backtrace;
+#[cfg(feature = "backtrace")]
+use std::sync::Arc;
+
+/// The operation performed on ManuallyDrop at the moment the violation was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ManuallyDropOperation {
+	/// ManuallyDrop::drop
+	Drop,
+	/// ManuallyDrop::take
+	Take,
+	/// ManuallyDrop::into_inner
+	IntoInner,
+	/// ManuallyDrop::ignore_drop (ManuallyDrop::forget)
+	IgnoreDrop,
+	/// Reading the value (deref, as_value, as_ptr, ...)
+	Deref,
+	/// The ManuallyDrop itself was dropped without releasing the value.
+	ImplicitDrop,
+	/// ManuallyDrop::reinit (a new value is written to the released ManuallyDrop)
+	Reinit,
+	/// ManuallyDrop::lend, ManuallyDrop::with_taken (the value is temporarily moved out)
+	Lend,
+	/// ManuallyDrop::replace (the old value is moved out, a new value is written)
+	Replace,
+}
+
+impl ManuallyDropOperation {
+	/// The number of operations.
+	pub const COUNT: usize = 9;
+
+	/// All operations, in the order of their indexes.
+	pub const ALL: [ManuallyDropOperation; Self::COUNT] = [
+		Self::Drop,
+		Self::Take,
+		Self::IntoInner,
+		Self::IgnoreDrop,
+		Self::Deref,
+		Self::ImplicitDrop,
+		Self::Reinit,
+		Self::Lend,
+		Self::Replace,
+	];
+
+	/// Index of the operation (`0..ManuallyDropOperation::COUNT`).
+	#[inline]
+	pub const fn index(self) -> usize {
+		self as usize
+	}
+}
+
+impl Display for ManuallyDropOperation {
+	#[inline]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		let str = match self {
+			Self::Drop => "drop",
+			Self::Take => "take",
+			Self::IntoInner => "into_inner",
+			Self::IgnoreDrop => "ignore_drop",
+			Self::Deref => "deref",
+			Self::ImplicitDrop => "implicit drop",
+			Self::Reinit => "reinit",
+			Self::Lend => "lend",
+			Self::Replace => "replace",
+		};
+
+		Display::fmt(str, f)
+	}
+}
+
+/// Kind of undefined behavior of ManuallyDrop.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+	/// The value has already been dropped and is dropped again.
+	DoubleDrop,
+	/// The value has already been taken (take, into_inner) and is dropped.
+	DropAfterTake,
+	/// The value has already been dropped and is taken (take, into_inner).
+	TakeAfterDrop,
+	/// The value has already been taken (take, into_inner) and is taken again.
+	DoubleTake,
+	/// The value has already been dropped and is read.
+	UseAfterDrop,
+	/// The value has already been taken (take, into_inner) and is read.
+	UseAfterTake,
+	/// The value was marked as forgotten (ignore_drop) and is used again.
+	UseAfterForget,
+	/// The value has already been released and is marked as forgotten (ignore_drop).
+	ForgetAfterRelease,
+	/// ManuallyDrop was dropped without releasing the value.
+	ImplicitDrop,
+	/// The value has not been released and is overwritten by a new value (reinit),
+	/// the old value would be leaked.
+	ReinitWithoutRelease,
+	/// The value is temporarily moved out (lend, with_taken) and is used
+	/// before it was put back.
+	UseWhileLent,
+	/// The destructor of the value is running and the value is used again
+	/// (re-entrant access from the destructor).
+	UseDuringDrop,
+	/// The destructor of the value panicked (the value was partly dropped)
+	/// and the value is used again.
+	UseAfterPoisonedDrop,
+	/// The state of ManuallyDrop is corrupted (`byte` is not a valid state, or the canary
+	/// next to the state is damaged), it is unknown whether the value was released.
+	StateCorrupted {
+		/// The status byte that was read.
+		byte: u8,
+	},
+	/// The violation was reported with only a message (`TrigManuallyDrop::trig_next_invalid_beh`),
+	/// its details are unknown.
+	Unknown,
+}
+
+impl ViolationKind {
+	/// The number of violation kinds.
+	pub const COUNT: usize = 15;
+
+	/// All violation kinds, in the order of their indexes
+	/// (`StateCorrupted` with the byte 0).
+	pub const ALL: [ViolationKind; Self::COUNT] = [
+		Self::DoubleDrop,
+		Self::DropAfterTake,
+		Self::TakeAfterDrop,
+		Self::DoubleTake,
+		Self::UseAfterDrop,
+		Self::UseAfterTake,
+		Self::UseAfterForget,
+		Self::ForgetAfterRelease,
+		Self::ImplicitDrop,
+		Self::ReinitWithoutRelease,
+		Self::UseWhileLent,
+		Self::UseDuringDrop,
+		Self::UseAfterPoisonedDrop,
+		Self::StateCorrupted { byte: 0 },
+		Self::Unknown,
+	];
+
+	/// Index of the violation kind (`0..ViolationKind::COUNT`),
+	/// for example, to keep a counter per kind.
+	#[inline]
+	pub const fn index(self) -> usize {
+		match self {
+			Self::DoubleDrop => 0,
+			Self::DropAfterTake => 1,
+			Self::TakeAfterDrop => 2,
+			Self::DoubleTake => 3,
+			Self::UseAfterDrop => 4,
+			Self::UseAfterTake => 5,
+			Self::UseAfterForget => 6,
+			Self::ForgetAfterRelease => 7,
+			Self::ImplicitDrop => 8,
+			Self::ReinitWithoutRelease => 9,
+			Self::UseWhileLent => 10,
+			Self::UseDuringDrop => 11,
+			Self::UseAfterPoisonedDrop => 12,
+			Self::StateCorrupted { .. } => 13,
+			Self::Unknown => 14,
+		}
+	}
+
+	/// Determine the kind of violation from the state of ManuallyDrop
+	/// and the operation that was attempted on it.
+	pub const fn from_state_and_operation(
+		state: StateManuallyDropData,
+		operation: ManuallyDropOperation,
+	) -> Self {
+		use ManuallyDropOperation as Op;
+		use StateManuallyDropData as State;
+
+		match (state, operation) {
+			(State::LentModeTrig, _) => Self::UseWhileLent,
+			(State::InDrop, _) => Self::UseDuringDrop,
+			(State::Poisoned, _) => Self::UseAfterPoisonedDrop,
+			(_, Op::Reinit) => Self::ReinitWithoutRelease,
+			(_, Op::ImplicitDrop) | (State::Empty, _) => Self::ImplicitDrop,
+			(State::IgnoreTrigWhenDrop, _) => Self::UseAfterForget,
+			(_, Op::IgnoreDrop) => Self::ForgetAfterRelease,
+
+			(State::DropModeTrig, Op::Drop) => Self::DoubleDrop,
+			(State::DropModeTrig, Op::Take | Op::IntoInner | Op::Lend | Op::Replace) => {
+				Self::TakeAfterDrop
+			}
+			(State::DropModeTrig, Op::Deref) => Self::UseAfterDrop,
+
+			(State::TakeModeTrig | State::IntoInnerModeTrig, Op::Drop) => Self::DropAfterTake,
+			(
+				State::TakeModeTrig | State::IntoInnerModeTrig,
+				Op::Take | Op::IntoInner | Op::Lend | Op::Replace,
+			) => Self::DoubleTake,
+			(State::TakeModeTrig | State::IntoInnerModeTrig, Op::Deref) => Self::UseAfterTake,
+		}
+	}
+}
+
+impl Display for ViolationKind {
+	#[inline]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		let str = match self {
+			Self::DoubleDrop => "double drop",
+			Self::DropAfterTake => "drop after take",
+			Self::TakeAfterDrop => "take after drop",
+			Self::DoubleTake => "double take",
+			Self::UseAfterDrop => "use after drop",
+			Self::UseAfterTake => "use after take",
+			Self::UseAfterForget => "use after ignore_drop",
+			Self::ForgetAfterRelease => "ignore_drop after release",
+			Self::ImplicitDrop => "implicit drop without release",
+			Self::ReinitWithoutRelease => "reinit without release",
+			Self::UseWhileLent => "use while lent",
+			Self::UseDuringDrop => "use during drop",
+			Self::UseAfterPoisonedDrop => "use after a panicked drop",
+			Self::StateCorrupted { byte } => {
+				return write!(f, "corrupted state ({:#04x})", byte);
+			}
+			Self::Unknown => "unknown violation",
+		};
+
+		Display::fmt(str, f)
+	}
+}
+
+/// Description of the undefined behavior detected by ManuallyDrop,
+/// passed to the trigger.
+///
+/// `Display` produces the same text that the triggers have always received
+/// (with the `backtrace` feature, followed by both backtraces).
+#[derive(Debug, Clone)]
+pub struct ViolationReport {
+	kind: ViolationKind,
+	/// None if the violation was reported with only a message.
+	state: Option<StateManuallyDropData>,
+	/// None if the violation was reported with only a message.
+	operation: Option<ManuallyDropOperation>,
+
+	type_name: &'static str,
+	address: usize,
+
+	release_location: Option<&'static Location<'static>>,
+	location: Option<&'static Location<'static>>,
+
+	/// The message of the violation reported with only a message (`ViolationKind::Unknown`).
+	#[cfg(feature = "alloc")]
+	message: Option<String>,
+
+	#[cfg(feature = "backtrace")]
+	release_backtrace: Option<Arc<Backtrace>>,
+	#[cfg(feature = "backtrace")]
+	backtrace: Arc<Backtrace>,
+}
+
+impl ViolationReport {
+	/// Create a violation report for ManuallyDrop<T> located at `address`
+	/// (null if the address is unknown), the kind of violation is determined by the state and the operation.
+	///
+	/// (With the `backtrace` feature, the backtrace of the caller is captured.)
+	#[inline]
+	pub fn new<T: ?Sized>(
+		address: *const (),
+		state: StateManuallyDropData,
+		operation: ManuallyDropOperation,
+		release_location: Option<&'static Location<'static>>,
+		location: Option<&'static Location<'static>>,
+	) -> Self {
+		Self {
+			kind: ViolationKind::from_state_and_operation(state, operation),
+			state: Some(state),
+			operation: Some(operation),
+
+			type_name: core::any::type_name::<T>(),
+			address: address as usize,
+
+			release_location,
+			location,
+
+			#[cfg(feature = "alloc")]
+			message: None,
+
+			#[cfg(feature = "backtrace")]
+			release_backtrace: None,
+			#[cfg(feature = "backtrace")]
+			backtrace: Arc::new(Backtrace::force_capture()),
+		}
+	}
+
+	/// Create a report of a violation reported with only a message
+	/// (`TrigManuallyDrop::trig_next_invalid_beh`), the kind of violation is `Unknown`.
+	///
+	/// The state, the operation, the type and the address are unknown
+	/// (None, None, `"<unknown>"` and 0), with the `alloc` feature
+	/// the report displays the message.
+	pub fn unknown(_message: Arguments<'_>) -> Self {
+		Self {
+			kind: ViolationKind::Unknown,
+			state: None,
+			operation: None,
+
+			type_name: "<unknown>",
+			address: 0,
+
+			release_location: None,
+			location: None,
+
+			#[cfg(feature = "alloc")]
+			message: Some(alloc::fmt::format(_message)),
+
+			#[cfg(feature = "backtrace")]
+			release_backtrace: None,
+			#[cfg(feature = "backtrace")]
+			backtrace: Arc::new(Backtrace::force_capture()),
+		}
+	}
+
+	/// Attach the backtrace captured when the value was released for the first time.
+	#[cfg(feature = "backtrace")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "backtrace")))]
+	#[inline]
+	pub fn with_release_backtrace(self, release_backtrace: Option<Arc<Backtrace>>) -> Self {
+		Self {
+			release_backtrace,
+			..self
+		}
+	}
+
+	/// The state of ManuallyDrop was corrupted (`byte` was read instead of a valid state),
+	/// the kind of violation is `StateCorrupted`.
+	#[inline]
+	pub fn with_corrupted_state(self, byte: u8) -> Self {
+		Self {
+			kind: ViolationKind::StateCorrupted { byte },
+			..self
+		}
+	}
+
+	/// Kind of violation.
+	#[inline]
+	pub const fn kind(&self) -> ViolationKind {
+		self.kind
+	}
+
+	/// The state of ManuallyDrop before the operation
+	/// (`Poisoned` if the state is corrupted, see `ViolationKind::StateCorrupted`,
+	/// None if the violation was reported with only a message, see `ViolationKind::Unknown`).
+	#[inline]
+	pub const fn state(&self) -> Option<StateManuallyDropData> {
+		self.state
+	}
+
+	/// The attempted operation
+	/// (None if the violation was reported with only a message, see `ViolationKind::Unknown`).
+	#[inline]
+	pub const fn operation(&self) -> Option<ManuallyDropOperation> {
+		self.operation
+	}
+
+	/// Name of the type of the value in ManuallyDrop (`core::any::type_name`).
+	#[inline]
+	pub const fn type_name(&self) -> &'static str {
+		self.type_name
+	}
+
+	/// Address of the ManuallyDrop instance (0 if it is unknown: for consuming
+	/// operations such as `into_inner`, the container was moved into the function).
+	#[inline]
+	pub const fn address(&self) -> usize {
+		self.address
+	}
+
+	/// The place in the code where the value was released for the first time.
+	#[inline]
+	pub const fn release_location(&self) -> Option<&'static Location<'static>> {
+		self.release_location
+	}
+
+	/// The place in the code where the violation was detected
+	/// (None for an implicit drop).
+	#[inline]
+	pub const fn location(&self) -> Option<&'static Location<'static>> {
+		self.location
+	}
+
+	/// The backtrace captured when the value was released for the first time.
+	#[cfg(feature = "backtrace")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "backtrace")))]
+	#[inline]
+	pub fn release_backtrace(&self) -> Option<&Backtrace> {
+		self.release_backtrace.as_deref()
+	}
+
+	/// The backtrace captured when the violation was detected.
+	#[cfg(feature = "backtrace")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "backtrace")))]
+	#[inline]
+	pub fn backtrace(&self) -> &Backtrace {
+		&self.backtrace
+	}
+}
+
+impl Display for ViolationReport {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		let (state, operation) = match (self.state, self.operation) {
+			(Some(state), Some(operation)) => (state, operation),
+			_ => {
+				#[cfg(feature = "alloc")]
+				if let Some(message) = &self.message {
+					Display::fmt(message, f)?;
+
+					return self.fmt_backtraces(f);
+				}
+
+				write!(
+					f,
+					"Undefined behavior when using ManuallyDrop (the details are unknown)."
+				)?;
+				return self.fmt_backtraces(f);
+			}
+		};
+
+		if let ViolationKind::StateCorrupted { byte } = self.kind {
+			write!(
+				f,
+				"Undefined behavior when using ManuallyDrop, the state is corrupted (state byte {:#04x}), it is unknown whether the value was released",
+				byte
+			)?;
+			match self.location {
+				Some(again) => write!(f, ", {} at {}:{}", operation, again.file(), again.line())?,
+				None => write!(f, ", {}", operation)?,
+			}
+
+			write!(f, ".")?;
+			return self.fmt_backtraces(f);
+		}
+
+		let transient_state = match state {
+			StateManuallyDropData::LentModeTrig => Some((
+				"the value is lent (lend, with_taken) and has not been put back yet",
+				"lent",
+			)),
+			StateManuallyDropData::InDrop => Some((
+				"the destructor of the value is running and the value is used again (re-entrancy)",
+				"drop started",
+			)),
+			StateManuallyDropData::Poisoned => Some((
+				"the destructor of the value panicked, the value was partly dropped (poisoned)",
+				"dropped",
+			)),
+			_ => None,
+		};
+		if let Some((description, release)) = transient_state {
+			write!(
+				f,
+				"Undefined behavior when using ManuallyDrop, {}",
+				description
+			)?;
+			if let Some(first) = self.release_location {
+				write!(f, ", {} at {}:{}", release, first.file(), first.line())?;
+			}
+			match self.location {
+				Some(again) => write!(f, ", {} at {}:{}", operation, again.file(), again.line())?,
+				None => write!(f, ", {}", operation)?,
+			}
+
+			write!(f, ".")?;
+			return self.fmt_backtraces(f);
+		}
+
+		match operation {
+			ManuallyDropOperation::ImplicitDrop => {
+				write!(
+					f,
+					"Undefined behavior when using ManuallyDrop (expected ManuallyDrop::drop(&mut value)), state should not be default, current state is {:?}.",
+					state
+				)?;
+
+				return self.fmt_backtraces(f);
+			}
+			ManuallyDropOperation::Reinit => {
+				write!(
+					f,
+					"Undefined behavior when using ManuallyDrop::reinit, the value has not been released and would be leaked, current state is {:?}",
+					state
+				)?;
+				if let Some(again) = self.location {
+					write!(f, ", reinit at {}:{}", again.file(), again.line())?;
+				}
+
+				write!(f, ".")?;
+				return self.fmt_backtraces(f);
+			}
+			ManuallyDropOperation::Deref => write!(
+				f,
+				"Undefined behavior when using ManuallyDrop.deref(), instead of the expected default state, the current state: {:?}",
+				state
+			)?,
+			_ => write!(
+				f,
+				"Undefined behavior when using ManuallyDrop(combo_replace_manudropstate), instead of the expected default state, the current state: {:?}",
+				state
+			)?,
+		}
+
+		write!(f, ", first {} at ", state.release_verb())?;
+		match self.release_location {
+			Some(first) => write!(f, "{}:{}", first.file(), first.line())?,
+			None => write!(f, "<unknown>")?,
+		}
+		if let Some(again) = self.location {
+			write!(f, ", used again at {}:{}", again.file(), again.line())?;
+		}
+
+		write!(f, ".")?;
+		self.fmt_backtraces(f)
+	}
+}
+
+impl ViolationReport {
+	/// Displays the backtraces of the report (only with the `backtrace` feature).
+	#[inline]
+	fn fmt_backtraces(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		#[cfg(feature = "backtrace")]
+		{
+			if let Some(release_backtrace) = &self.release_backtrace {
+				write!(
+					f,
+					"\n\nfirst {}, backtrace:\n{}",
+					self.state.map_or("released", |a| a.release_verb()),
+					release_backtrace
+				)?;
+			}
+			write!(f, "\n\nused again, backtrace:\n{}", self.backtrace)?;
+		}
+
+		#[cfg(not(feature = "backtrace"))]
+		let _f = f;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+#[test]
+fn test_violation_kind_index() {
+	for (index, kind) in ViolationKind::ALL.iter().enumerate() {
+		assert_eq!(kind.index(), index);
+	}
+}
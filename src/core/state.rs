@@ -1,37 +1,241 @@
 //! AtomicStates for ManuallyDrop
 
+#[cfg(feature = "leak_registry")]
+use crate::core::registry::LiveEntry;
+use crate::core::report::ManuallyDropOperation;
+use crate::core::report::ViolationReport;
+#[cfg(feature = "test_macro")]
+use crate::core::testing::TestScope;
 use crate::core::trig::TrigManuallyDrop;
 use crate::extended_debug_assertions::extended_debug_assertions;
+use core::cell::Cell;
 use core::fmt::Debug;
 use core::fmt::Display;
+use core::panic::Location;
+#[cfg(any(
+	feature = "state_canary",
+	feature = "track_location",
+	feature = "test_macro",
+	feature = "leak_registry"
+))]
+use core::ptr;
+#[cfg(any(
+	feature = "track_location",
+	feature = "test_macro",
+	feature = "leak_registry"
+))]
+use core::sync::atomic::AtomicPtr;
 use core::sync::atomic::AtomicU8;
 use core::sync::atomic::Ordering;
+#[cfg(feature = "backtrace")]
+use std::backtrace::Backtrace;
+#[cfg(any(
+	feature = "backtrace",
+	feature = "test_macro",
+	feature = "leak_registry"
+))]
+use std::sync::Arc;
+
+mod sealed {
+	pub trait Sealed {}
+
+	impl Sealed for core::sync::atomic::AtomicU8 {}
+	impl Sealed for core::cell::Cell<u8> {}
+}
+
+/// Storage of the state byte of ManuallyDrop: `AtomicU8` (default, ManuallyDrop can be
+/// shared between threads) or `Cell<u8>` (without atomic operations, cheaper on every check,
+/// ManuallyDrop is `!Sync`).
+pub trait StateStorage: sealed::Sealed {
+	// clippy::declare_interior_mutable_const why?: This constant is only used for initialization, no one is going to constantly access it for use.
+	#[allow(clippy::declare_interior_mutable_const)]
+	/// Storage with the empty state, needed only for const functions.
+	const EMPTY: Self;
+
+	/// Create a storage with the state byte.
+	fn new(byte: u8) -> Self;
+
+	/// Read the state byte.
+	fn load(&self) -> u8;
+
+	/// Replace the state byte, returns the previous one.
+	fn swap(&self, byte: u8) -> u8;
+
+	/// Replace the state byte if it is equal to `current`,
+	/// returns the previous one (`Err` if it was not replaced).
+	fn compare_exchange(&self, current: u8, new: u8) -> Result<u8, u8>;
+}
+
+impl StateStorage for AtomicU8 {
+	// clippy::declare_interior_mutable_const why?: This constant is only used for initialization, no one is going to constantly access it for use.
+	#[allow(clippy::declare_interior_mutable_const)]
+	const EMPTY: Self = AtomicU8::new(StateManuallyDropData::empty() as _);
+
+	#[inline(always)]
+	fn new(byte: u8) -> Self {
+		AtomicU8::new(byte)
+	}
+
+	#[inline(always)]
+	fn load(&self) -> u8 {
+		AtomicU8::load(self, StateManuallyDropData::READ_ORDERING_METHOD)
+	}
+
+	#[inline(always)]
+	fn swap(&self, byte: u8) -> u8 {
+		AtomicU8::swap(self, byte, StateManuallyDropData::WRITE_ORDERING_METHOD)
+	}
+
+	#[inline(always)]
+	fn compare_exchange(&self, current: u8, new: u8) -> Result<u8, u8> {
+		AtomicU8::compare_exchange(
+			self,
+			current,
+			new,
+			StateManuallyDropData::WRITE_ORDERING_METHOD,
+			StateManuallyDropData::READ_ORDERING_METHOD,
+		)
+	}
+}
+
+impl StateStorage for Cell<u8> {
+	// clippy::declare_interior_mutable_const why?: This constant is only used for initialization, no one is going to constantly access it for use.
+	#[allow(clippy::declare_interior_mutable_const)]
+	const EMPTY: Self = Cell::new(StateManuallyDropData::empty() as _);
+
+	#[inline(always)]
+	fn new(byte: u8) -> Self {
+		Cell::new(byte)
+	}
+
+	#[inline(always)]
+	fn load(&self) -> u8 {
+		self.get()
+	}
+
+	#[inline(always)]
+	fn swap(&self, byte: u8) -> u8 {
+		self.replace(byte)
+	}
+
+	#[inline(always)]
+	fn compare_exchange(&self, current: u8, new: u8) -> Result<u8, u8> {
+		let old = self.get();
+		if old == current {
+			self.set(new);
+
+			Ok(old)
+		} else {
+			Err(old)
+		}
+	}
+}
 
-/// Atomic safe states for ManuallyDrop
-#[repr(transparent)]
-pub struct StateManuallyDrop {
-	state: AtomicU8,
+/// Magic value of the canary next to the state byte (`state_canary`).
+#[cfg(feature = "state_canary")]
+const STATE_CANARY: u32 = 0x5AFE_4D44;
+
+/// Safe states for ManuallyDrop, the state byte is stored in `S`
+/// (`AtomicU8` by default, see `StateStorage`).
+#[cfg_attr(
+	not(any(
+		feature = "state_canary",
+		feature = "track_location",
+		feature = "test_macro",
+		feature = "leak_registry"
+	)),
+	repr(transparent)
+)]
+pub struct StateManuallyDrop<S: StateStorage = AtomicU8> {
+	/// Magic value next to the state byte, a stray write over the state
+	/// almost certainly damages it (`state_canary`).
+	#[cfg(feature = "state_canary")]
+	canary: u32,
+	state: S,
+	/// The place in the code where the value left the `Empty` state
+	/// (null while the state is `Empty`, `track_location`).
+	#[cfg(feature = "track_location")]
+	location: AtomicPtr<Location<'static>>,
+	/// The backtrace captured when the value left the `Empty` state
+	/// (`Arc::into_raw`, null while the state is `Empty`).
+	#[cfg(feature = "backtrace")]
+	backtrace: AtomicPtr<Backtrace>,
+	/// The tracking scope of the test in which the value was created
+	/// (`Arc::into_raw`, null outside of `#[SafeManuallyDrop::test]`).
+	#[cfg(feature = "test_macro")]
+	test_scope: AtomicPtr<TestScope>,
+	/// The entry of the value in the registry of live ManuallyDrop
+	/// (`Arc::into_raw`, null if the value is not registered).
+	#[cfg(feature = "leak_registry")]
+	live_entry: AtomicPtr<LiveEntry>,
 }
 
-impl Clone for StateManuallyDrop {
+impl<S: StateStorage> Clone for StateManuallyDrop<S> {
 	#[inline]
 	fn clone(&self) -> Self {
 		Self {
-			state: AtomicU8::new(self.__read_byte()),
+			#[cfg(feature = "state_canary")]
+			canary: STATE_CANARY,
+			state: S::new(self.__read_byte()),
+			#[cfg(feature = "track_location")]
+			location: AtomicPtr::new(self.__read_location_ptr()),
+			#[cfg(feature = "backtrace")]
+			backtrace: AtomicPtr::new(match self.read_backtrace() {
+				Some(a) => Arc::into_raw(a) as *mut _,
+				None => ptr::null_mut(),
+			}),
+			// the copy is not a ManuallyDrop created in the test.
+			#[cfg(feature = "test_macro")]
+			test_scope: AtomicPtr::new(ptr::null_mut()),
+			#[cfg(feature = "leak_registry")]
+			live_entry: AtomicPtr::new(ptr::null_mut()),
 		}
 	}
 }
 
-impl Debug for StateManuallyDrop {
+#[cfg(any(
+	feature = "backtrace",
+	feature = "test_macro",
+	feature = "leak_registry"
+))]
+impl<S: StateStorage> Drop for StateManuallyDrop<S> {
+	#[inline]
+	fn drop(&mut self) {
+		#[cfg(feature = "backtrace")]
+		self.__replace_backtrace(None);
+
+		#[cfg(feature = "test_macro")]
+		{
+			let test_scope = *self.test_scope.get_mut();
+			if !test_scope.is_null() {
+				// safe, the pointer was obtained from `Arc::into_raw`.
+				drop(unsafe { Arc::from_raw(test_scope) });
+			}
+		}
+
+		#[cfg(feature = "leak_registry")]
+		{
+			let live_entry = *self.live_entry.get_mut();
+			if !live_entry.is_null() {
+				// safe, the pointer was obtained from `Arc::into_raw`.
+				let live_entry = unsafe { Arc::from_raw(live_entry) };
+				live_entry.unregister();
+			}
+		}
+	}
+}
+
+impl<S: StateStorage> Debug for StateManuallyDrop<S> {
 	#[inline]
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
 		f.debug_struct("StateManuallyDrop")
 			.field("state", &self.read())
+			.field("location", &self.read_location())
 			.finish()
 	}
 }
 
-impl Display for StateManuallyDrop {
+impl<S: StateStorage> Display for StateManuallyDrop<S> {
 	#[inline(always)]
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
 		Display::fmt(&self.read(), f)
@@ -59,6 +263,18 @@ pub enum StateManuallyDropData {
 	/// (unsafe/manual_behavior) ManuallyDrop must be forgotten, subsequent work
 	/// with ManuallyDrop will definitely call the trigger.
 	IgnoreTrigWhenDrop = 30,
+
+	/// With the lend and with_taken functions, the value is temporarily moved out and
+	/// will be put back, any work with ManuallyDrop before that will call the trigger.
+	LentModeTrig = 35,
+
+	/// The destructor of the value is running (drop), any work with ManuallyDrop
+	/// before the end of the destructor (re-entrancy) will call the trigger.
+	InDrop = 40,
+
+	/// The destructor of the value panicked, the value was partly dropped,
+	/// any work with ManuallyDrop will call the trigger.
+	Poisoned = 45,
 }
 
 impl Display for StateManuallyDropData {
@@ -72,6 +288,11 @@ impl Display for StateManuallyDropData {
 			Self::IntoInnerModeTrig => "IntoInnerModeTrig",
 
 			Self::IgnoreTrigWhenDrop => "IgnoreTrigWhenDrop",
+
+			Self::LentModeTrig => "LentModeTrig",
+
+			Self::InDrop => "InDrop",
+			Self::Poisoned => "Poisoned",
 		};
 
 		Display::fmt(str, f)
@@ -103,7 +324,7 @@ impl StateManuallyDropData {
 	}
 
 	/// Create a state from a byte, in case of an error,
-	/// return the default state.
+	/// return the default state (the corrupted byte is not detected, see `from_or_corrupted`).
 	#[inline]
 	pub fn from_or_empty(a: u8) -> Self {
 		Self::validate_with_fns(a, || unsafe { Self::unchecked_from(a) }, Self::empty)
@@ -123,6 +344,12 @@ impl StateManuallyDropData {
 		)
 	}
 
+	/// Create a state from a byte, the invalid (corrupted) byte is returned as an error.
+	#[inline]
+	pub fn from_or_corrupted(a: u8) -> Result<Self, u8> {
+		Self::validate_with_fns(a, || Ok(unsafe { Self::unchecked_from(a) }), || Err(a))
+	}
+
 	/// Create default state
 	#[inline(always)]
 	pub const fn empty() -> Self {
@@ -145,7 +372,10 @@ impl StateManuallyDropData {
 				|| a == Self::TakeModeTrig as _
 				|| a == Self::DropModeTrig as _
 				|| a == Self::IntoInnerModeTrig as _
-				|| a == Self::IgnoreTrigWhenDrop as _ =>
+				|| a == Self::IgnoreTrigWhenDrop as _
+				|| a == Self::LentModeTrig as _
+				|| a == Self::InDrop as _
+				|| a == Self::Poisoned as _ =>
 			{
 				next()
 			}
@@ -155,6 +385,10 @@ impl StateManuallyDropData {
 
 	/// Create a state from a byte quickly and without checks
 	/// (important, the byte is checked anyway, but only in a debug build)
+	///
+	/// # Safety
+	///
+	/// The byte must be a valid state (`StateManuallyDropData as u8`).
 	#[inline]
 	pub unsafe fn unchecked_from(a: u8) -> Self {
 		extended_debug_assertions!(Self::validate_with_fns(a, || true, || false), true);
@@ -186,6 +420,44 @@ impl StateManuallyDropData {
 	pub const fn is_empty(&self) -> bool {
 		matches!(self, StateManuallyDropData::Empty)
 	}
+
+	/// Whether the value is temporarily moved out (lend, with_taken).
+	#[inline]
+	pub const fn is_lent(&self) -> bool {
+		matches!(self, StateManuallyDropData::LentModeTrig)
+	}
+
+	/// Whether the destructor of the value is running (drop).
+	#[inline]
+	pub const fn is_in_drop(&self) -> bool {
+		matches!(self, StateManuallyDropData::InDrop)
+	}
+
+	/// Whether the destructor of the value panicked (the value was partly dropped).
+	#[inline]
+	pub const fn is_poisoned(&self) -> bool {
+		matches!(self, StateManuallyDropData::Poisoned)
+	}
+
+	/// How the value left the `Empty` state, used in trigger messages
+	/// ("first dropped at ...").
+	#[inline]
+	pub const fn release_verb(&self) -> &'static str {
+		match self {
+			Self::Empty => "used",
+
+			Self::TakeModeTrig => "taken",
+			Self::DropModeTrig => "dropped",
+			Self::IntoInnerModeTrig => "extracted (into_inner)",
+
+			Self::IgnoreTrigWhenDrop => "forgotten (ignore_drop)",
+
+			Self::LentModeTrig => "lent (lend, with_taken)",
+
+			Self::InDrop => "dropped (the destructor is running)",
+			Self::Poisoned => "dropped (the destructor panicked)",
+		}
+	}
 }
 
 impl StateManuallyDrop {
@@ -197,7 +469,15 @@ impl StateManuallyDrop {
 	/// Create default state
 	#[inline]
 	pub fn empty() -> Self {
-		let sself = Self::EMPTY_STATE;
+		Self::new_empty()
+	}
+}
+
+impl<S: StateStorage> StateManuallyDrop<S> {
+	/// Create default state (with any state storage).
+	#[inline]
+	pub fn new_empty() -> Self {
+		let sself = Self::__empty();
 
 		extended_debug_assertions!(sself.is_empty(), true);
 		extended_debug_assertions!(sself.is_next_trig(), false);
@@ -207,142 +487,719 @@ impl StateManuallyDrop {
 
 	/// Create default state
 	#[inline]
-	const fn __empty() -> Self {
+	pub(crate) const fn __empty() -> Self {
 		Self {
-			state: AtomicU8::new(StateManuallyDropData::empty() as _),
+			#[cfg(feature = "state_canary")]
+			canary: STATE_CANARY,
+			state: S::EMPTY,
+			#[cfg(feature = "track_location")]
+			location: AtomicPtr::new(ptr::null_mut()),
+			#[cfg(feature = "backtrace")]
+			backtrace: AtomicPtr::new(ptr::null_mut()),
+			#[cfg(feature = "test_macro")]
+			test_scope: AtomicPtr::new(ptr::null_mut()),
+			#[cfg(feature = "leak_registry")]
+			live_entry: AtomicPtr::new(ptr::null_mut()),
 		}
 	}
 
+	/// A new ManuallyDrop<T> was created (`new`): attach it to the tracking scope of the test
+	/// (`test_macro`) and register it in the registry of live ManuallyDrop (`leak_registry`).
+	#[cfg(any(feature = "test_macro", feature = "leak_registry"))]
+	#[doc(hidden)]
+	#[track_caller]
+	#[inline]
+	pub fn __created<T: ?Sized>(&self) {
+		#[cfg(feature = "test_macro")]
+		self.__enter_test_scope();
+
+		#[cfg(feature = "leak_registry")]
+		{
+			let live_entry = LiveEntry::register::<T>(Location::caller());
+			let old = self.live_entry.swap(
+				Arc::into_raw(live_entry) as *mut _,
+				StateManuallyDropData::WRITE_ORDERING_METHOD,
+			);
+			if !old.is_null() {
+				// safe, the pointer was obtained from `Arc::into_raw`.
+				let old = unsafe { Arc::from_raw(old) };
+				old.unregister();
+			}
+		}
+	}
+
+	/// The entry of the value in the registry of live ManuallyDrop.
+	#[cfg(feature = "leak_registry")]
+	#[inline]
+	fn __live_entry(&self) -> Option<&LiveEntry> {
+		let live_entry = self
+			.live_entry
+			.load(StateManuallyDropData::READ_ORDERING_METHOD);
+
+		// safe, the pointer was obtained from `Arc::into_raw` and lives as long as the state.
+		unsafe { live_entry.as_ref() }
+	}
+
+	/// Attach the new ManuallyDrop to the tracking scope of the test running
+	/// in the current thread (`#[SafeManuallyDrop::test]`), if there is one.
+	#[cfg(feature = "test_macro")]
+	#[inline]
+	fn __enter_test_scope(&self) {
+		if let Some(scope) = TestScope::current() {
+			scope.add_created();
+
+			let old = self.test_scope.swap(
+				Arc::into_raw(scope) as *mut _,
+				StateManuallyDropData::WRITE_ORDERING_METHOD,
+			);
+			if !old.is_null() {
+				// safe, the pointer was obtained from `Arc::into_raw`.
+				drop(unsafe { Arc::from_raw(old) });
+			}
+		}
+	}
+
+	/// The tracking scope of the test in which the value was created.
+	#[cfg(feature = "test_macro")]
+	#[inline]
+	fn __test_scope(&self) -> Option<&TestScope> {
+		let test_scope = self
+			.test_scope
+			.load(StateManuallyDropData::READ_ORDERING_METHOD);
+
+		// safe, the pointer was obtained from `Arc::into_raw` and lives as long as the state.
+		unsafe { test_scope.as_ref() }
+	}
+
 	/// Whether the current state is like a new unused object.
 	#[inline]
 	pub fn is_empty(&self) -> bool {
 		self.read().is_empty()
 	}
 
+	/// Whether the value is temporarily moved out (lend, with_taken).
+	#[inline]
+	pub fn is_lent(&self) -> bool {
+		self.read().is_lent()
+	}
+
+	/// Whether the destructor of the value is running (drop).
+	#[inline]
+	pub fn is_in_drop(&self) -> bool {
+		self.read().is_in_drop()
+	}
+
+	/// Whether the destructor of the value panicked (the value was partly dropped).
+	#[inline]
+	pub fn is_poisoned(&self) -> bool {
+		self.read().is_poisoned()
+	}
+
 	/// Getting the status byte of the current ManuallyDrop.
 	#[inline]
 	fn __read_byte(&self) -> u8 {
-		self.state.load(StateManuallyDropData::READ_ORDERING_METHOD)
+		self.state.load()
 	}
 
 	/// Getting the status of the current ManuallyDrop.
+	///
+	/// The corrupted state (see `read_corrupted_byte`) is read as `Poisoned`,
+	/// the value must be neither used nor dropped.
 	#[inline]
 	pub fn read(&self) -> StateManuallyDropData {
+		self.__state_from_byte(self.__read_byte())
+	}
+
+	/// The state of the byte read from the current ManuallyDrop, `Poisoned` if it is corrupted.
+	#[inline]
+	fn __state_from_byte(&self, byte: u8) -> StateManuallyDropData {
+		match StateManuallyDropData::from_or_corrupted(byte) {
+			Ok(a) if !self.__is_damaged_canary() => a,
+			_ => StateManuallyDropData::Poisoned,
+		}
+	}
+
+	/// The status byte of the current ManuallyDrop if the state is corrupted (the byte is
+	/// not a valid state, for example, after a buffer overflow or a stray `ptr::write`,
+	/// or with the `state_canary` feature, the canary next to the state is damaged).
+	#[inline]
+	pub fn read_corrupted_byte(&self) -> Option<u8> {
 		let byte = self.__read_byte();
 
-		unsafe { StateManuallyDropData::unchecked_from(byte) }
+		match StateManuallyDropData::from_or_corrupted(byte) {
+			Ok(_) if !self.__is_damaged_canary() => None,
+			_ => Some(byte),
+		}
+	}
+
+	/// Whether the canary next to the state was overwritten (`state_canary`).
+	#[inline(always)]
+	fn __is_damaged_canary(&self) -> bool {
+		#[cfg(feature = "state_canary")]
+		{
+			// volatile, the canary is only ever written by a memory corruption.
+			unsafe { ptr::read_volatile(&self.canary) != STATE_CANARY }
+		}
+
+		#[cfg(not(feature = "state_canary"))]
+		{
+			false
+		}
+	}
+
+	/// Getting the raw location pointer of the current ManuallyDrop.
+	#[cfg(feature = "track_location")]
+	#[inline]
+	fn __read_location_ptr(&self) -> *mut Location<'static> {
+		self.location
+			.load(StateManuallyDropData::READ_ORDERING_METHOD)
+	}
+
+	/// Remember the place in the code where the value was released
+	/// (None when the state is reset, `track_location`).
+	#[inline(always)]
+	fn __write_location(&self, _location: Option<&'static Location<'static>>) {
+		#[cfg(feature = "track_location")]
+		self.location.store(
+			match _location {
+				Some(a) => a as *const Location<'static> as *mut _,
+				None => ptr::null_mut(),
+			},
+			StateManuallyDropData::WRITE_ORDERING_METHOD,
+		);
+	}
+
+	/// The place in the code where the value was released for the first time
+	/// (None if the state is empty, always None without the `track_location` feature).
+	#[inline]
+	pub fn read_location(&self) -> Option<&'static Location<'static>> {
+		#[cfg(feature = "track_location")]
+		{
+			let location = self.__read_location_ptr();
+
+			// safe, only `&'static Location<'static>` is ever written to the pointer.
+			unsafe { location.as_ref() }
+		}
+
+		#[cfg(not(feature = "track_location"))]
+		{
+			None
+		}
+	}
+
+	/// The backtrace captured when the value was released for the first time
+	/// (None if the state is empty).
+	#[cfg(feature = "backtrace")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "backtrace")))]
+	pub fn read_backtrace(&self) -> Option<Arc<Backtrace>> {
+		let backtrace = self
+			.backtrace
+			.load(StateManuallyDropData::READ_ORDERING_METHOD);
+		if backtrace.is_null() {
+			return None;
+		}
+
+		// safe, the pointer was obtained from `Arc::into_raw` and is released
+		// only in `get_and_reset` and `drop`.
+		unsafe {
+			Arc::increment_strong_count(backtrace);
+			Some(Arc::from_raw(backtrace))
+		}
+	}
+
+	/// Replace the backtrace of the first release, the previous one is released.
+	#[cfg(feature = "backtrace")]
+	fn __replace_backtrace(&self, a: Option<Arc<Backtrace>>) {
+		let new = match a {
+			Some(a) => Arc::into_raw(a) as *mut _,
+			None => ptr::null_mut(),
+		};
+		let old = self
+			.backtrace
+			.swap(new, StateManuallyDropData::WRITE_ORDERING_METHOD);
+
+		if !old.is_null() {
+			// safe, the pointer was obtained from `Arc::into_raw`.
+			drop(unsafe { Arc::from_raw(old) });
+		}
+	}
+
+	/// Create a report on the violation detected in the current state.
+	#[inline]
+	fn __report<T: ?Sized>(
+		&self,
+		instance: *const (),
+		state: StateManuallyDropData,
+		operation: ManuallyDropOperation,
+		location: Option<&'static Location<'static>>,
+	) -> ViolationReport {
+		let report =
+			ViolationReport::new::<T>(instance, state, operation, self.read_location(), location);
+		let report = match self.read_corrupted_byte() {
+			Some(byte) => report.with_corrupted_state(byte),
+			None => report,
+		};
+
+		#[cfg(feature = "backtrace")]
+		let report = report.with_release_backtrace(self.read_backtrace());
+
+		report
+	}
+
+	/// Execute the trigger for the violation detected in the current state
+	/// (with `std`, the violation is captured instead if `testing::expect_violation` is active).
+	#[inline(never)]
+	#[cold]
+	fn __violation<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+		state: StateManuallyDropData,
+		operation: ManuallyDropOperation,
+		location: Option<&'static Location<'static>>,
+	) {
+		let report = self.__report::<T>(instance, state, operation, location);
+
+		#[cfg(feature = "std")]
+		if crate::core::testing::__capture_violation(&report) {
+			return;
+		}
+
+		#[cfg(feature = "test_macro")]
+		match self.__test_scope() {
+			Some(scope) => scope.add_violation(&report),
+			None => {
+				if let Some(scope) = TestScope::current() {
+					scope.add_violation(&report);
+				}
+			}
+		}
+
+		Trig::trig_violation(&report);
 	}
 
 	/// Quick substitution of the state of the current ManuallyDrop
 	/// (note that the previous state of ManuallyDrop is returned)
 	#[inline]
 	fn __force_write(&self, a: StateManuallyDropData) -> StateManuallyDropData {
-		let byte = self
-			.state
-			.swap(a as _, StateManuallyDropData::WRITE_ORDERING_METHOD);
+		let byte = self.state.swap(a as _);
 
-		unsafe { StateManuallyDropData::unchecked_from(byte) }
+		self.__state_from_byte(byte)
 	}
 
 	/// Resets the ManuallyDrop state to the initial state
+	///
+	/// # Safety
+	///
+	/// The checks of the previous state are lost, the value must be valid (not released, or written again) before ManuallyDrop is used.
 	pub unsafe fn get_and_reset(&self) -> StateManuallyDropData {
 		let old_value = self.__force_write(StateManuallyDropData::Empty);
+		self.__write_location(None);
+		#[cfg(feature = "backtrace")]
+		self.__replace_backtrace(None);
+		#[cfg(feature = "test_macro")]
+		if !old_value.is_empty() {
+			// the value must be released again.
+			if let Some(scope) = self.__test_scope() {
+				scope.add_created();
+			}
+		}
+		#[cfg(feature = "leak_registry")]
+		if !old_value.is_empty() {
+			let live_entry = self
+				.live_entry
+				.load(StateManuallyDropData::READ_ORDERING_METHOD);
+			if !live_entry.is_null() {
+				// safe, the pointer was obtained from `Arc::into_raw` and lives as long as the state.
+				let live_entry = core::mem::ManuallyDrop::new(unsafe { Arc::from_raw(live_entry) });
+				live_entry.reregister();
+			}
+		}
 		extended_debug_assertions!(self.is_empty(), true);
 		extended_debug_assertions!(self.is_next_trig(), false);
 
 		old_value
 	}
 
-	/// Function to safely replace the state of the ManuallyDrop trigger
-	/// definer (note that the new state must fire on validation)
+	/// Atomically replace the empty state of ManuallyDrop with a new state and
+	/// remember the location of the caller, or return the current state if it is
+	/// not empty (nothing is written in this case, so that the first release is
+	/// always the one reported).
+	#[track_caller]
 	#[inline]
-	fn __safe_replace_mutstate<Trig: TrigManuallyDrop>(&self, new_state: StateManuallyDropData) {
+	fn __try_replace_mutstate(
+		&self,
+		new_state: StateManuallyDropData,
+	) -> Result<(), StateManuallyDropData> {
 		extended_debug_assertions!(new_state.is_next_trig(), true);
+		if self.__is_damaged_canary() {
+			return Err(StateManuallyDropData::Poisoned);
+		}
+
+		match self
+			.state
+			.compare_exchange(StateManuallyDropData::Empty as _, new_state as _)
+		{
+			Ok(_) => {
+				self.__write_location(Some(Location::caller()));
+				#[cfg(feature = "backtrace")]
+				self.__replace_backtrace(Some(Arc::new(Backtrace::force_capture())));
+				#[cfg(feature = "test_macro")]
+				if let Some(scope) = self.__test_scope() {
+					scope.add_released();
+				}
+				#[cfg(feature = "leak_registry")]
+				if let Some(live_entry) = self.__live_entry() {
+					live_entry.unregister();
+				}
+
+				Ok(())
+			}
+			Err(old_byte) => Err(self.__state_from_byte(old_byte)),
+		}
+	}
 
-		let old_state = self.__force_write(new_state);
+	/// Function to safely replace the state of the ManuallyDrop trigger
+	/// definer (note that the new state must fire on validation),
+	/// returns whether the operation can be performed.
+	#[track_caller]
+	#[inline]
+	fn __safe_replace_mutstate<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+		new_state: StateManuallyDropData,
+		operation: ManuallyDropOperation,
+	) -> bool {
+		match self.__try_replace_mutstate(new_state) {
+			Ok(()) => true,
+			Err(old_state) => {
+				// COMBO REPLACE STATE -> ERR
+				self.__violation::<Trig, T>(
+					instance,
+					old_state,
+					operation,
+					Some(Location::caller()),
+				);
+
+				// the trigger is not fatal and returned, the operation is skipped (recovery mode)
+				false
+			}
+		}
+	}
 
-		// COMBO REPLACE STATE -> ERR
-		if old_state.is_next_trig() {
-			Trig::trig_next_invalid_beh(
-				format_args!(
-					"Undefined behavior when using ManuallyDrop(combo_replace_manudropstate), instead of the expected default state, the current state: {:?}.", 
-					old_state
-				)
-			);
+	/// The operation after a violation can be neither performed nor skipped
+	/// (the trigger is not fatal and returned), refuse it with a panic.
+	#[doc(hidden)]
+	#[track_caller]
+	#[cold]
+	#[inline(never)]
+	pub fn __refuse_operation(&self, operation: ManuallyDropOperation) -> ! {
+		let state = self.read();
+
+		match self.read_location() {
+			Some(location) => panic!(
+				"ManuallyDrop {} refused, the value was already {} at {}:{}, the current state: {:?}.",
+				operation,
+				state.release_verb(),
+				location.file(),
+				location.line(),
+				state
+			),
+			None => panic!(
+				"ManuallyDrop {} refused, the value was already {}, the current state: {:?}.",
+				operation,
+				state.release_verb(),
+				state
+			),
 		}
 	}
 
 	/// Change the ManuallyDrop state to a panicked state, or execute a trigger
 	/// function if the current state was not empty.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
 	#[inline(always)]
-	pub fn to_dropmode_or_trig<Trig: TrigManuallyDrop>(&self) {
-		self.__safe_replace_mutstate::<Trig>(StateManuallyDropData::DropModeTrig);
+	pub fn to_dropmode_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+	) -> bool {
+		let is_valid = self.__safe_replace_mutstate::<Trig, T>(
+			instance,
+			StateManuallyDropData::DropModeTrig,
+			ManuallyDropOperation::Drop,
+		);
 
 		extended_debug_assertions!(self.is_next_trig(), true);
+		is_valid
+	}
+
+	/// Change the ManuallyDrop state to the state of the running destructor (see `__in_drop`),
+	/// or execute a trigger function if the current state was not empty.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
+	#[inline(always)]
+	pub fn to_indropmode_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+	) -> bool {
+		let is_valid = self.__safe_replace_mutstate::<Trig, T>(
+			instance,
+			StateManuallyDropData::InDrop,
+			ManuallyDropOperation::Drop,
+		);
+
+		extended_debug_assertions!(self.is_next_trig(), true);
+		is_valid
+	}
+
+	/// Run the destructor of the value in the `InDrop` state (`to_indropmode_or_trig`),
+	/// then the state of the dropped value is set, or the `Poisoned` state
+	/// if the destructor panicked.
+	#[doc(hidden)]
+	#[inline]
+	pub fn __in_drop(&self, drop: impl FnOnce()) {
+		struct PoisonOnUnwind<'a, S: StateStorage>(&'a StateManuallyDrop<S>);
+
+		impl<'a, S: StateStorage> Drop for PoisonOnUnwind<'a, S> {
+			#[inline]
+			fn drop(&mut self) {
+				self.0.__force_write(StateManuallyDropData::Poisoned);
+			}
+		}
+
+		let on_unwind = PoisonOnUnwind(self);
+		drop();
+		core::mem::forget(on_unwind);
+
+		let _old_state = self.__force_write(StateManuallyDropData::DropModeTrig);
+		extended_debug_assertions!(_old_state.is_in_drop(), true);
 	}
 
 	/// Change the state of ManuallyDrop to the state of the released value,
 	/// or execute the trigger function if the current state was not empty.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
 	#[inline]
-	pub fn to_takemode_or_trig<Trig: TrigManuallyDrop>(&self) {
-		self.__safe_replace_mutstate::<Trig>(StateManuallyDropData::TakeModeTrig);
+	pub fn to_takemode_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+	) -> bool {
+		let is_valid = self.__safe_replace_mutstate::<Trig, T>(
+			instance,
+			StateManuallyDropData::TakeModeTrig,
+			ManuallyDropOperation::Take,
+		);
 
 		extended_debug_assertions!(self.is_next_trig(), true);
+		is_valid
+	}
+
+	/// Change the state of ManuallyDrop to the state of the lent value (the value is
+	/// temporarily moved out and will be put back with `__return_lent`), or execute
+	/// the trigger function if the current state was not empty.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
+	#[inline]
+	pub fn to_lentmode_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+	) -> bool {
+		let is_valid = self.__safe_replace_mutstate::<Trig, T>(
+			instance,
+			StateManuallyDropData::LentModeTrig,
+			ManuallyDropOperation::Lend,
+		);
+
+		extended_debug_assertions!(self.is_next_trig(), true);
+		is_valid
+	}
+
+	/// The lent value was put back, resets the ManuallyDrop state to the initial state.
+	#[inline]
+	pub(crate) fn __return_lent(&self) {
+		let _old_state = unsafe { self.get_and_reset() };
+
+		extended_debug_assertions!(_old_state.is_lent(), true);
+	}
+
+	/// The lent value was lost (the function that took it panicked),
+	/// the value is considered taken.
+	#[inline]
+	pub(crate) fn __lost_lent(&self) {
+		let _old_state = self.__force_write(StateManuallyDropData::TakeModeTrig);
+
+		extended_debug_assertions!(_old_state.is_lent(), true);
 	}
 
 	/// Change the ManuallyDrop state to ignore freeing the value, or execute the
 	/// trigger function if the current state was not empty.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
 	#[inline]
-	pub fn to_ignore_trig_when_drop<Trig: TrigManuallyDrop>(&self) {
-		self.__safe_replace_mutstate::<Trig>(StateManuallyDropData::IgnoreTrigWhenDrop);
+	pub fn to_ignore_trig_when_drop<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+	) -> bool {
+		let is_valid = self.__safe_replace_mutstate::<Trig, T>(
+			instance,
+			StateManuallyDropData::IgnoreTrigWhenDrop,
+			ManuallyDropOperation::IgnoreDrop,
+		);
 
 		extended_debug_assertions!(self.is_next_trig(), true);
+		is_valid
 	}
 
 	/// Change the state of ManuallyDrop to the state of the released value, or execute
 	/// the trigger function if the current state was not empty.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
 	#[inline]
-	pub fn to_intoinnermode_or_trig<Trig: TrigManuallyDrop>(&self) {
-		self.__safe_replace_mutstate::<Trig>(StateManuallyDropData::IntoInnerModeTrig);
+	pub fn to_intoinnermode_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+	) -> bool {
+		let is_valid = self.__safe_replace_mutstate::<Trig, T>(
+			instance,
+			StateManuallyDropData::IntoInnerModeTrig,
+			ManuallyDropOperation::IntoInner,
+		);
 
 		extended_debug_assertions!(self.is_next_trig(), true);
+		is_valid
+	}
+
+	/// Check that the value of ManuallyDrop has not been released before an operation that
+	/// does not change the state (for example, `replace`), or execute the trigger.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
+	#[inline]
+	pub fn check_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(
+		&self,
+		instance: *const (),
+		operation: ManuallyDropOperation,
+	) -> bool {
+		let a_state = self.read();
+
+		if a_state.is_next_trig() {
+			self.__violation::<Trig, T>(instance, a_state, operation, Some(Location::caller()));
+
+			return false;
+		}
+
+		true
+	}
+
+	/// Check that the value of ManuallyDrop has been released before a new value is
+	/// written (`reinit`), or execute the trigger (the old value would be leaked).
+	/// A corrupted state (see `read_corrupted_byte`) is never reset, it is unknown
+	/// whether the value was released.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the new value must not be written.
+	#[track_caller]
+	#[inline]
+	pub fn reinit_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(&self, instance: *const ()) -> bool {
+		let a_state = self.read();
+
+		if a_state.is_empty()
+			|| a_state.is_lent()
+			|| a_state.is_in_drop()
+			|| self.read_corrupted_byte().is_some()
+		{
+			self.__violation::<Trig, T>(
+				instance,
+				a_state,
+				ManuallyDropOperation::Reinit,
+				Some(Location::caller()),
+			);
+
+			return false;
+		}
+
+		true
 	}
 
 	/// Check the state of ManuallyDrop for a readable state, or execute a trigger
 	/// function if the current state was not empty.
+	///
+	/// Returns false if the trigger was executed and returned (non-fatal trigger),
+	/// in this case the operation must be skipped.
+	#[track_caller]
 	#[inline]
-	pub fn deref_or_trig<Trig: TrigManuallyDrop>(&self) {
+	pub fn deref_or_trig<Trig: TrigManuallyDrop, T: ?Sized>(&self, instance: *const ()) -> bool {
 		let a_state = self.read();
 
 		if a_state.is_next_trig() {
-			Trig::trig_next_invalid_beh(
-				format_args!(
-					"Undefined behavior when using ManuallyDrop.deref(), instead of the expected default state, the current state: {:?}.",
-					a_state
-				)
-			)
+			self.__violation::<Trig, T>(
+				instance,
+				a_state,
+				ManuallyDropOperation::Deref,
+				Some(Location::caller()),
+			);
+
+			return false;
 		}
+
+		#[cfg(feature = "leak_registry")]
+		if let Some(live_entry) = self.__live_entry() {
+			live_entry.seen_at(instance);
+		}
+
+		true
 	}
 
-	/// Check if the ManuallyDrop state is empty, or execute the trigger function if
-	/// the current state was not empty.
-	pub fn if_empty_then_run_trigfn<Trig: TrigManuallyDrop, F: FnOnce()>(
+	/// Check if the ManuallyDrop state is empty (the value was not released), in this case
+	/// `fn_trig` (dropping the value) and the trigger are executed according to
+	/// the implicit drop policy of the trigger (`TrigManuallyDrop::IMPLICIT_DROP_POLICY`).
+	pub fn if_empty_then_run_trigfn<Trig: TrigManuallyDrop, T: ?Sized, F: FnOnce()>(
 		&self,
-		exp_str: &'static str,
+		instance: *const (),
 		fn_trig: F,
 	) {
 		let a_state = self.read();
 
 		if a_state.is_empty() {
-			fn_trig();
+			// the violation is reported, not the leak.
+			#[cfg(feature = "test_macro")]
+			if let Some(scope) = self.__test_scope() {
+				scope.add_released();
+			}
+
+			let policy = Trig::IMPLICIT_DROP_POLICY;
+			if policy.is_drop_value() {
+				fn_trig();
+			}
 
-			Trig::trig_next_invalid_beh(
-				format_args!(
-					"Undefined behavior when using ManuallyDrop ({}), state should not be default, current state is {:?}.",
-					exp_str,
-					a_state
-				)
-			)
+			if policy.is_trig() {
+				self.__violation::<Trig, T>(
+					instance,
+					a_state,
+					ManuallyDropOperation::ImplicitDrop,
+					None,
+				);
+			}
+		} else if self.read_corrupted_byte().is_some() {
+			// it is unknown whether the value was released, the value is leaked.
+			self.__violation::<Trig, T>(
+				instance,
+				a_state,
+				ManuallyDropOperation::ImplicitDrop,
+				None,
+			);
 		}
 	}
 
@@ -351,12 +1208,115 @@ impl StateManuallyDrop {
 	pub fn is_next_trig(&self) -> bool {
 		self.read().is_next_trig()
 	}
+
+	/// Atomically change the empty state of ManuallyDrop to the state of the
+	/// dropped value, without executing the trigger.
+	#[track_caller]
+	#[inline]
+	pub fn try_to_dropmode(&self) -> Result<(), ManuallyDropError> {
+		self.__try_replace_mutstate(StateManuallyDropData::DropModeTrig)
+			.map_err(|state| ManuallyDropError::new(state, self.read_location()))
+	}
+
+	/// Atomically change the empty state of ManuallyDrop to the state of the
+	/// running destructor (see `__in_drop`), without executing the trigger.
+	#[track_caller]
+	#[inline]
+	pub fn try_to_indropmode(&self) -> Result<(), ManuallyDropError> {
+		self.__try_replace_mutstate(StateManuallyDropData::InDrop)
+			.map_err(|state| ManuallyDropError::new(state, self.read_location()))
+	}
+
+	/// Atomically change the empty state of ManuallyDrop to the state of the
+	/// taken value, without executing the trigger.
+	#[track_caller]
+	#[inline]
+	pub fn try_to_takemode(&self) -> Result<(), ManuallyDropError> {
+		self.__try_replace_mutstate(StateManuallyDropData::TakeModeTrig)
+			.map_err(|state| ManuallyDropError::new(state, self.read_location()))
+	}
+
+	/// Atomically change the empty state of ManuallyDrop to the state of the
+	/// extracted value, without executing the trigger.
+	#[track_caller]
+	#[inline]
+	pub fn try_to_intoinnermode(&self) -> Result<(), ManuallyDropError> {
+		self.__try_replace_mutstate(StateManuallyDropData::IntoInnerModeTrig)
+			.map_err(|state| ManuallyDropError::new(state, self.read_location()))
+	}
+
+	/// Check the state of ManuallyDrop for a readable state,
+	/// without executing the trigger.
+	#[inline]
+	pub fn try_deref(&self) -> Result<(), ManuallyDropError> {
+		let a_state = self.read();
+
+		match a_state.is_next_trig() {
+			true => Err(ManuallyDropError::new(a_state, self.read_location())),
+			false => Ok(()),
+		}
+	}
+}
+
+/// The operation on ManuallyDrop cannot be performed in its current state
+/// (returned by the `try_*` functions instead of executing the trigger).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ManuallyDropError {
+	state: StateManuallyDropData,
+	release_location: Option<&'static Location<'static>>,
+}
+
+impl ManuallyDropError {
+	/// Create an error for the current state of ManuallyDrop.
+	#[inline]
+	pub const fn new(
+		state: StateManuallyDropData,
+		release_location: Option<&'static Location<'static>>,
+	) -> Self {
+		Self {
+			state,
+			release_location,
+		}
+	}
+
+	/// The current state of ManuallyDrop.
+	#[inline]
+	pub const fn state(&self) -> StateManuallyDropData {
+		self.state
+	}
+
+	/// The place in the code where the value was released for the first time.
+	#[inline]
+	pub const fn release_location(&self) -> Option<&'static Location<'static>> {
+		self.release_location
+	}
+}
+
+impl Display for ManuallyDropError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		write!(
+			f,
+			"ManuallyDrop is not in the default state, the current state: {:?}",
+			self.state
+		)?;
+		if let Some(first) = self.release_location {
+			write!(
+				f,
+				", first {} at {}:{}",
+				self.state.release_verb(),
+				first.file(),
+				first.line()
+			)?;
+		}
+
+		write!(f, ".")
+	}
 }
 
-impl Default for StateManuallyDrop {
+impl<S: StateStorage> Default for StateManuallyDrop<S> {
 	#[inline(always)]
 	fn default() -> Self {
-		StateManuallyDrop::empty()
+		Self::new_empty()
 	}
 }
 
@@ -373,7 +1333,7 @@ mod tests {
 		assert!(state.is_empty());
 		assert!(!state.is_next_trig());
 
-		state.deref_or_trig::<PanicTrigManuallyDrop>(); // ok
+		state.deref_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null()); // ok
 	}
 
 	#[test]
@@ -383,7 +1343,7 @@ mod tests {
 		assert!(state.is_empty());
 		assert!(!state.is_next_trig());
 
-		state.deref_or_trig::<PanicTrigManuallyDrop>(); // ok
+		state.deref_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null()); // ok
 	}
 
 	#[test]
@@ -393,8 +1353,8 @@ mod tests {
 		assert!(state.is_empty());
 		assert!(!state.is_next_trig());
 
-		state.deref_or_trig::<PanicTrigManuallyDrop>(); // ok
-		state.to_dropmode_or_trig::<PanicTrigManuallyDrop>();
+		state.deref_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null()); // ok
+		state.to_dropmode_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null());
 
 		assert!(!state.is_empty());
 		assert!(state.is_next_trig());
@@ -406,4 +1366,168 @@ mod tests {
 		assert!(old_state.is_next_trig());
 		assert_eq!(old_state, StateManuallyDropData::DropModeTrig);
 	}
+
+	#[cfg(not(any(
+		feature = "state_canary",
+		feature = "track_location",
+		feature = "test_macro",
+		feature = "leak_registry"
+	)))]
+	#[test]
+	fn test_state_size() {
+		assert_eq!(core::mem::size_of::<StateManuallyDrop>(), 1);
+		assert_eq!(
+			core::mem::size_of::<StateManuallyDrop<core::cell::Cell<u8>>>(),
+			1
+		);
+	}
+
+	#[cfg(feature = "track_location")]
+	#[test]
+	fn test_release_location() {
+		let state = StateManuallyDrop::empty();
+		assert_eq!(state.read_location(), None);
+
+		let line = line!() + 1;
+		state.to_takemode_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null());
+
+		let location = state.read_location().unwrap();
+		assert_eq!(location.file(), file!());
+		assert_eq!(location.line(), line);
+
+		let _old_state = unsafe { state.get_and_reset() };
+		assert_eq!(state.read_location(), None);
+	}
+
+	#[test]
+	fn test_try_state() {
+		let state = StateManuallyDrop::empty();
+		assert_eq!(state.try_deref(), Ok(()));
+		assert_eq!(state.try_to_takemode(), Ok(()));
+
+		let err = state.try_to_dropmode().unwrap_err();
+		assert_eq!(err.state(), StateManuallyDropData::TakeModeTrig);
+		#[cfg(feature = "track_location")]
+		assert!(err.release_location().is_some());
+		assert_eq!(state.try_deref(), Err(err));
+
+		// the failed attempt does not change the state
+		assert_eq!(state.read(), StateManuallyDropData::TakeModeTrig);
+	}
+
+	#[test]
+	fn test_cell_state() {
+		let state = StateManuallyDrop::<core::cell::Cell<u8>>::new_empty();
+		assert!(state.is_empty());
+		assert_eq!(state.try_deref(), Ok(()));
+		assert_eq!(state.try_to_dropmode(), Ok(()));
+
+		let err = state.try_to_takemode().unwrap_err();
+		assert_eq!(err.state(), StateManuallyDropData::DropModeTrig);
+		assert_eq!(state.try_deref(), Err(err));
+
+		let old_state = unsafe { state.get_and_reset() };
+		assert_eq!(old_state, StateManuallyDropData::DropModeTrig);
+		assert!(state.is_empty());
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn test_corrupted_state() {
+		use crate::core::report::ViolationKind;
+		use crate::core::state::StateStorage;
+		use crate::core::testing::expect_violation;
+
+		let state = StateManuallyDrop::empty();
+		StateStorage::swap(&state.state, 0xAB); // <-- stray write
+		assert_eq!(state.read_corrupted_byte(), Some(0xAB));
+		assert_eq!(state.read(), StateManuallyDropData::Poisoned);
+
+		let report = expect_violation(|| {
+			state.deref_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null());
+		});
+		assert_eq!(report.kind(), ViolationKind::StateCorrupted { byte: 0xAB });
+		assert!(report.to_string().contains("state byte 0xab"));
+
+		let report = expect_violation(|| {
+			state.to_dropmode_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null());
+		});
+		assert_eq!(report.kind(), ViolationKind::StateCorrupted { byte: 0xAB });
+
+		// the value is leaked
+		let mut is_dropped = false;
+		let report = expect_violation(|| {
+			state.if_empty_then_run_trigfn::<PanicTrigManuallyDrop, (), _>(
+				core::ptr::null(),
+				|| is_dropped = true,
+			);
+		});
+		assert_eq!(report.kind(), ViolationKind::StateCorrupted { byte: 0xAB });
+		assert!(!is_dropped);
+
+		// the corrupted state is not reset by reinit
+		let report = expect_violation(|| {
+			assert!(!state.reinit_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null()));
+		});
+		assert_eq!(report.kind(), ViolationKind::StateCorrupted { byte: 0xAB });
+		assert_eq!(state.read_corrupted_byte(), Some(0xAB));
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn test_reinit_corrupted_state() {
+		use crate::beh::safe::SafeManuallyDrop;
+		use crate::core::report::ViolationKind;
+		use crate::core::state::StateStorage;
+		use crate::core::testing::expect_violation;
+		use crate::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+
+		type ManuallyDrop<T> = SafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+		let mut slot = ManuallyDrop::new(vec![1, 2, 3]);
+		StateStorage::swap(&slot.state.state, 0xAB); // <-- stray write
+
+		// it is unknown whether the value was released, the state is not reset
+		let report = expect_violation(|| ManuallyDrop::reinit(&mut slot, vec![4]));
+		assert_eq!(report.kind(), ViolationKind::StateCorrupted { byte: 0xAB });
+		assert_eq!(slot.state.read_corrupted_byte(), Some(0xAB));
+
+		let report = expect_violation(|| {
+			let _value = ManuallyDrop::get_or_reinit(&mut slot, || vec![5]);
+		});
+		assert_eq!(report.kind(), ViolationKind::StateCorrupted { byte: 0xAB });
+		assert_eq!(slot.state.read_corrupted_byte(), Some(0xAB));
+
+		// the value is leaked
+		let report = expect_violation(|| drop(slot));
+		assert_eq!(report.kind(), ViolationKind::StateCorrupted { byte: 0xAB });
+	}
+
+	#[cfg(all(feature = "std", feature = "state_canary"))]
+	#[test]
+	fn test_damaged_canary() {
+		use crate::core::report::ViolationKind;
+		use crate::core::testing::expect_violation;
+
+		let mut state = StateManuallyDrop::empty();
+		state.canary = 0; // <-- stray write
+		assert_eq!(
+			state.read_corrupted_byte(),
+			Some(StateManuallyDropData::Empty as u8)
+		);
+		assert_eq!(
+			state.try_to_dropmode().unwrap_err().state(),
+			StateManuallyDropData::Poisoned
+		);
+
+		let report = expect_violation(|| {
+			state.to_takemode_or_trig::<PanicTrigManuallyDrop, ()>(core::ptr::null());
+		});
+		assert_eq!(
+			report.kind(),
+			ViolationKind::StateCorrupted {
+				byte: StateManuallyDropData::Empty as u8
+			}
+		);
+	}
 }
@@ -0,0 +1,297 @@
+//! Helpers for testing code that uses ManuallyDrop: capture the violation
+//! of the current thread instead of executing the trigger.
+//!
+//! ```rust
+//! use SafeManuallyDrop::core::report::ViolationKind;
+//! use SafeManuallyDrop::core::testing::{expect_no_violation, expect_violation};
+//! // (the loop trigger would hang the thread, the violation is captured instead)
+//! use SafeManuallyDrop::AlwaysSafeEmptyLoopManuallyDrop as ManuallyDrop;
+//!
+//! let report = expect_violation(|| {
+//! 	let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+//!
+//! 	#[allow(unused_unsafe)]
+//! 	unsafe {
+//! 		ManuallyDrop::drop(&mut data);
+//! 		ManuallyDrop::drop(&mut data);
+//! 	}
+//! });
+//! assert_eq!(report.kind(), ViolationKind::DoubleDrop);
+//!
+//! let len = expect_no_violation(|| {
+//! 	let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+//!
+//! 	#[allow(unused_unsafe)]
+//! 	unsafe {
+//! 		ManuallyDrop::take(&mut data).len()
+//! 	}
+//! });
+//! assert_eq!(len, 3);
+//! ```
+
+use crate::core::report::ViolationReport;
+use std::cell::RefCell;
+use std::panic::catch_unwind;
+use std::panic::resume_unwind;
+use std::panic::AssertUnwindSafe;
+#[cfg(feature = "test_macro")]
+use std::sync::atomic::AtomicUsize;
+#[cfg(feature = "test_macro")]
+use std::sync::atomic::Ordering;
+#[cfg(feature = "test_macro")]
+use std::sync::Arc;
+#[cfg(feature = "test_macro")]
+use std::sync::Mutex;
+
+std::thread_local! {
+	/// The capture slot of the current thread, `Some` while `expect_violation`
+	/// or `expect_no_violation` is active (contains the first captured report).
+	static CAPTURE: RefCell<Option<Option<ViolationReport>>> = const { RefCell::new(None) };
+}
+
+/// The payload of the unwinding used to stop the body after a captured violation.
+struct ViolationCaptured;
+
+/// Capture the violation if the capture slot of the current thread is active, then stop the
+/// invalid operation by unwinding.
+///
+/// Returns false if the capture is not active and the trigger must be executed. While the thread
+/// is already panicking, the operation cannot be stopped by unwinding again: the violations are
+/// skipped only during the unwinding after the captured violation, otherwise the trigger is executed.
+#[doc(hidden)]
+pub fn __capture_violation(report: &ViolationReport) -> bool {
+	if std::thread::panicking() {
+		// a violation was already captured, the body is unwinding after it
+		return CAPTURE
+			.try_with(|capture| matches!(&*capture.borrow(), Some(Some(_))))
+			.unwrap_or(false);
+	}
+
+	let is_captured = CAPTURE
+		.try_with(|capture| match &mut *capture.borrow_mut() {
+			Some(slot) => {
+				if slot.is_none() {
+					*slot = Some(report.clone());
+				}
+
+				true
+			}
+			None => false,
+		})
+		.unwrap_or(false);
+
+	if is_captured {
+		// without the panic hook, the captured violation is not a panic of the test.
+		resume_unwind(Box::new(ViolationCaptured));
+	}
+
+	is_captured
+}
+
+/// Activates the capture slot of the current thread, restores the previous one when dropped
+/// (nested captures are supported).
+struct CaptureGuard {
+	previous: Option<Option<ViolationReport>>,
+}
+
+impl CaptureGuard {
+	#[inline]
+	fn new() -> Self {
+		Self {
+			previous: CAPTURE.with(|capture| capture.borrow_mut().replace(None)),
+		}
+	}
+
+	/// Deactivate the capture slot, returning the captured report.
+	#[inline]
+	fn take(mut self) -> Option<ViolationReport> {
+		let previous = self.previous.take();
+		let report =
+			CAPTURE.with(|capture| core::mem::replace(&mut *capture.borrow_mut(), previous));
+		core::mem::forget(self);
+
+		report.flatten()
+	}
+}
+
+impl Drop for CaptureGuard {
+	#[inline]
+	fn drop(&mut self) {
+		let previous = self.previous.take();
+		let _e = CAPTURE.try_with(|capture| *capture.borrow_mut() = previous);
+	}
+}
+
+/// Execute `body` and return the report of the first violation of ManuallyDrop in the
+/// current thread, the trigger is not executed, the body is stopped at the violation.
+///
+/// Panics if there was no violation (a panic of the body is passed on).
+/// Note that violations are detected only by the safe ManuallyDrop.
+#[track_caller]
+pub fn expect_violation(body: impl FnOnce()) -> ViolationReport {
+	let guard = CaptureGuard::new();
+	let result = catch_unwind(AssertUnwindSafe(body));
+	let report = guard.take();
+
+	match (report, result) {
+		(Some(report), _) => report,
+		(None, Err(e)) => resume_unwind(e),
+		(None, Ok(())) => panic!("Expected a ManuallyDrop violation, but none occurred."),
+	}
+}
+
+/// Execute `body` and check that there was no violation of ManuallyDrop
+/// in the current thread, returns the result of the body.
+///
+/// Panics with the report if there was a violation (the trigger is not executed).
+#[track_caller]
+pub fn expect_no_violation<R>(body: impl FnOnce() -> R) -> R {
+	let guard = CaptureGuard::new();
+	let result = catch_unwind(AssertUnwindSafe(body));
+	let report = guard.take();
+
+	if let Some(report) = report {
+		panic!("Unexpected ManuallyDrop violation: {}", report);
+	}
+
+	match result {
+		Ok(a) => a,
+		Err(e) => resume_unwind(e),
+	}
+}
+
+/// Tracking scope of a test marked with `#[SafeManuallyDrop::test]`: counts the ManuallyDrop
+/// created in the test and released (or ignore_dropped), collects all violations.
+#[cfg(feature = "test_macro")]
+pub(crate) struct TestScope {
+	created: AtomicUsize,
+	released: AtomicUsize,
+	violations: Mutex<Vec<ViolationReport>>,
+}
+
+#[cfg(feature = "test_macro")]
+impl TestScope {
+	#[inline]
+	const fn new() -> Self {
+		Self {
+			created: AtomicUsize::new(0),
+			released: AtomicUsize::new(0),
+			violations: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// The tracking scope of the current thread (if a test scope is active).
+	#[inline]
+	pub(crate) fn current() -> Option<Arc<TestScope>> {
+		TEST_SCOPE
+			.try_with(|scope| scope.borrow().clone())
+			.ok()
+			.flatten()
+	}
+
+	/// A ManuallyDrop was created (or reset to the initial state) in the scope.
+	#[inline]
+	pub(crate) fn add_created(&self) {
+		self.created.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// A ManuallyDrop of the scope left the initial state (released, ignore_dropped
+	/// or implicitly dropped).
+	#[inline]
+	pub(crate) fn add_released(&self) {
+		self.released.fetch_add(1, Ordering::Relaxed);
+	}
+
+	/// Remember the violation detected in the scope.
+	pub(crate) fn add_violation(&self, report: &ViolationReport) {
+		let mut violations = match self.violations.lock() {
+			Ok(a) => a,
+			Err(e) => e.into_inner(),
+		};
+
+		violations.push(report.clone());
+	}
+
+	/// Panics if there were violations in the scope, or if some ManuallyDrop was not released.
+	#[track_caller]
+	fn check(&self) {
+		let violations = match self.violations.lock() {
+			Ok(mut a) => core::mem::take(&mut *a),
+			Err(e) => core::mem::take(&mut *e.into_inner()),
+		};
+		if !violations.is_empty() {
+			let mut message = format!(
+				"{} ManuallyDrop violation(s) detected during the test:",
+				violations.len()
+			);
+			for report in violations {
+				message.push_str("\n - ");
+				message.push_str(&report.to_string());
+			}
+
+			panic!("{}", message);
+		}
+
+		let created = self.created.load(Ordering::Relaxed);
+		let released = self.released.load(Ordering::Relaxed);
+		if created > released {
+			panic!(
+				"{} of {} ManuallyDrop created during the test were neither released nor ignore_dropped (leak).",
+				created - released,
+				created
+			);
+		}
+	}
+}
+
+#[cfg(feature = "test_macro")]
+std::thread_local! {
+	/// The tracking scope of the test running in the current thread.
+	static TEST_SCOPE: RefCell<Option<Arc<TestScope>>> = const { RefCell::new(None) };
+}
+
+/// Activates the tracking scope of the current thread, restores the previous one when dropped.
+#[cfg(feature = "test_macro")]
+struct TestScopeGuard {
+	previous: Option<Arc<TestScope>>,
+}
+
+#[cfg(feature = "test_macro")]
+impl TestScopeGuard {
+	#[inline]
+	fn new(scope: Arc<TestScope>) -> Self {
+		Self {
+			previous: TEST_SCOPE.with(|a| a.borrow_mut().replace(scope)),
+		}
+	}
+}
+
+#[cfg(feature = "test_macro")]
+impl Drop for TestScopeGuard {
+	#[inline]
+	fn drop(&mut self) {
+		let previous = self.previous.take();
+		let _e = TEST_SCOPE.try_with(|a| *a.borrow_mut() = previous);
+	}
+}
+
+/// Execute the test `body` in a tracking scope (this is what `#[SafeManuallyDrop::test]`
+/// expands to), returns the result of the body.
+///
+/// Panics if a violation of ManuallyDrop was detected during the body (even if the trigger is
+/// not fatal), or if a ManuallyDrop created in the current thread during the body was neither
+/// released nor ignore_dropped. A ManuallyDrop created in the body is tracked in any thread,
+/// other threads do not inherit the scope, so tests running in parallel do not interfere.
+#[cfg(feature = "test_macro")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test_macro")))]
+#[track_caller]
+pub fn run_in_test_scope<R>(body: impl FnOnce() -> R) -> R {
+	let scope = Arc::new(TestScope::new());
+
+	let guard = TestScopeGuard::new(scope.clone());
+	let result = body();
+	drop(guard);
+
+	scope.check();
+	result
+}
@@ -1,4 +1,5 @@
 use crate::core::trig::TrigManuallyDrop;
+use core::convert::Infallible;
 use core::fmt::Arguments;
 
 /// A protected version of ManuallyDrop with a function to
@@ -16,12 +17,14 @@ pub type AutoSafeAbortManuallyDrop<T> =
 pub enum AbortTrigManuallyDrop {}
 
 impl TrigManuallyDrop for AbortTrigManuallyDrop {
-	fn trig_next_invalid_beh(a: Arguments<'_>) -> trig_manuallydrop_returntype!() {
+	type Output = Infallible;
+
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> Infallible {
 		use std::io::Write;
 
 		{
 			let mut lock = std::io::stderr().lock();
-			let _e = write!(lock, "{}\n", a);
+			let _e = writeln!(lock, "{}", a);
 			let _e = lock.flush();
 		}
 
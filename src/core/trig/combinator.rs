@@ -0,0 +1,454 @@
+//! Triggers built from other triggers: `Chain`, `AfterN` and `OncePerSite`.
+//!
+//! The state of `AfterN` and `OncePerSite` (the number of violations, the call sites seen)
+//! is kept in a domain `D` given explicitly, so that unrelated combinators do not share it.
+//!
+//! ```rust
+//! use SafeManuallyDrop::core::trig::combinator::{AfterN, OncePerSite, TrigDomain, TrigDomainState};
+//! use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+//! use SafeManuallyDrop::core::trig::TrigManuallyDrop;
+//! use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+//! use core::fmt::Arguments;
+//!
+//! /// Logs the violation and continues.
+//! enum LogTrig {}
+//!
+//! impl TrigManuallyDrop for LogTrig {
+//! 	type Output = ();
+//!
+//! 	fn trig_next_invalid_beh(a: Arguments<'_>) {
+//! 		eprintln!("ManuallyDrop: {}", a);
+//! 	}
+//! }
+//!
+//! enum StagingDomain {}
+//!
+//! impl TrigDomain for StagingDomain {
+//! 	fn state() -> &'static TrigDomainState {
+//! 		static STATE: TrigDomainState = TrigDomainState::new();
+//!
+//! 		&STATE
+//! 	}
+//! }
+//!
+//! // Log the first violation from each site, stop the thread after 100 violations.
+//! type StagingTrig = AfterN<
+//! 	100,
+//! 	OncePerSite<LogTrig, StagingDomain>,
+//! 	EmptyLoopTrigManuallyDrop,
+//! 	StagingDomain,
+//! >;
+//!
+//! let data = AlwaysSafeManuallyDrop::<_, StagingTrig>::new(1);
+//! # drop(data);
+//! ```
+
+use crate::core::report::ViolationReport;
+use crate::core::trig::policy::ImplicitDropPolicy;
+use crate::core::trig::TrigManuallyDrop;
+use core::fmt::Arguments;
+use core::fmt::Write;
+use core::marker::PhantomData;
+use core::panic::Location;
+use core::ptr;
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+
+/// The state of the combinators (`AfterN`, `OncePerSite`) of one domain.
+pub struct TrigDomainState {
+	/// The number of violations seen by `AfterN`.
+	violations: AtomicU64,
+
+	/// The call sites already seen by `OncePerSite`.
+	sites: [AtomicPtr<Location<'static>>; TrigDomainState::SITES],
+	/// The hashes of the messages (violations without a report) and of the types
+	/// of the implicit drops (violations without a call site) already seen by `OncePerSite`.
+	messages: [AtomicU64; TrigDomainState::SITES],
+}
+
+impl TrigDomainState {
+	/// The maximum number of call sites remembered by `OncePerSite`,
+	/// after the table is full, violations from new sites are always passed on.
+	pub const SITES: usize = 64;
+
+	const DEF_SETORDERING: Ordering = Ordering::SeqCst;
+	const DEF_GETORDERING: Ordering = Ordering::Acquire;
+
+	/// Create an empty state.
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			violations: AtomicU64::new(0),
+
+			sites: [const { AtomicPtr::new(ptr::null_mut()) }; Self::SITES],
+			messages: [const { AtomicU64::new(0) }; Self::SITES],
+		}
+	}
+
+	/// Add a violation, returns the number of violations before it.
+	#[inline]
+	pub fn next_violation(&self) -> u64 {
+		self.violations.fetch_add(1, Self::DEF_SETORDERING)
+	}
+
+	/// The number of violations seen by `AfterN`.
+	#[inline]
+	pub fn violations(&self) -> u64 {
+		self.violations.load(Self::DEF_GETORDERING)
+	}
+
+	/// Remember the call site, returns true if it has not been seen before
+	/// (or the table is full). An implicit drop has no call site, it is remembered
+	/// by the type of the value (`type_name`).
+	pub fn insert_site(&self, site: Option<&'static Location<'static>>, type_name: &str) -> bool {
+		let site = match site {
+			Some(a) => a,
+			None => {
+				let mut hasher = MessageHasher::new();
+				let _e = hasher.write_str("implicit drop of ");
+				let _e = hasher.write_str(type_name);
+
+				return self.insert_hash(hasher.finish());
+			}
+		};
+		let site_ptr = site as *const Location<'static> as *mut Location<'static>;
+
+		for slot in self.sites.iter() {
+			let mut current = slot.load(Self::DEF_GETORDERING);
+			if current.is_null() {
+				match slot.compare_exchange(
+					ptr::null_mut(),
+					site_ptr,
+					Self::DEF_SETORDERING,
+					Self::DEF_GETORDERING,
+				) {
+					Ok(_) => return true,
+					Err(a) => current = a,
+				}
+			}
+
+			// the same Location may be stored in different places, compare the contents.
+			if unsafe { &*current } == site {
+				return false;
+			}
+		}
+
+		true
+	}
+
+	/// Remember the message of a violation without a report (its call site is unknown),
+	/// returns true if it has not been seen before (or the table is full).
+	pub fn insert_message(&self, message: Arguments<'_>) -> bool {
+		let mut hasher = MessageHasher::new();
+		let _e = hasher.write_fmt(message);
+
+		self.insert_hash(hasher.finish())
+	}
+
+	/// Remember the hash, returns true if it has not been seen before (or the table is full).
+	fn insert_hash(&self, hash: u64) -> bool {
+		for slot in self.messages.iter() {
+			let current = match slot.compare_exchange(
+				0,
+				hash,
+				Self::DEF_SETORDERING,
+				Self::DEF_GETORDERING,
+			) {
+				Ok(_) => return true,
+				Err(a) => a,
+			};
+
+			if current == hash {
+				return false;
+			}
+		}
+
+		true
+	}
+
+	/// Forget all violations, call sites and messages.
+	pub fn reset(&self) {
+		self.violations.store(0, Self::DEF_SETORDERING);
+		for slot in self.sites.iter() {
+			slot.store(ptr::null_mut(), Self::DEF_SETORDERING);
+		}
+		for slot in self.messages.iter() {
+			slot.store(0, Self::DEF_SETORDERING);
+		}
+	}
+}
+
+impl Default for TrigDomainState {
+	#[inline(always)]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// FNV-1a hash of a formatted message (without allocation).
+struct MessageHasher(u64);
+
+impl MessageHasher {
+	#[inline]
+	const fn new() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+
+	/// The hash of the message, never 0 (0 is an empty slot).
+	#[inline]
+	const fn finish(&self) -> u64 {
+		match self.0 {
+			0 => 1,
+			a => a,
+		}
+	}
+}
+
+impl Write for MessageHasher {
+	#[inline]
+	fn write_str(&mut self, s: &str) -> core::fmt::Result {
+		for byte in s.bytes() {
+			self.0 ^= byte as u64;
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+
+		Ok(())
+	}
+}
+
+/// A domain of the combinator state, each domain has its own static state,
+/// combinators of the same domain share it.
+///
+/// ```rust
+/// use SafeManuallyDrop::core::trig::combinator::{TrigDomain, TrigDomainState};
+///
+/// enum MyPoolDomain {}
+///
+/// impl TrigDomain for MyPoolDomain {
+/// 	fn state() -> &'static TrigDomainState {
+/// 		static STATE: TrigDomainState = TrigDomainState::new();
+///
+/// 		&STATE
+/// 	}
+/// }
+/// ```
+pub trait TrigDomain {
+	/// The state of the domain.
+	fn state() -> &'static TrigDomainState;
+}
+
+/// The global domain of the combinator state, shared by all combinators that use it
+/// (for example, a single budget of violations for the whole program).
+pub enum GlobalTrigDomain {}
+
+impl TrigDomain for GlobalTrigDomain {
+	#[inline(always)]
+	fn state() -> &'static TrigDomainState {
+		static GLOBAL_TRIG_DOMAIN_STATE: TrigDomainState = TrigDomainState::new();
+
+		&GLOBAL_TRIG_DOMAIN_STATE
+	}
+}
+
+/// Executes the trigger `A` and then the trigger `B` (for example, count and then hook),
+/// `B` is not executed if `A` is fatal.
+///
+/// The implicit drop policy is the policy of `B` (the trigger whose result is returned),
+/// the policy of `A` is ignored, wrap the chain in `WithImplicitDropPolicy` to choose another one.
+pub struct Chain<A, B>(PhantomData<(A, B)>);
+
+impl<A: TrigManuallyDrop, B: TrigManuallyDrop> TrigManuallyDrop for Chain<A, B> {
+	type Output = B::Output;
+
+	const IMPLICIT_DROP_POLICY: ImplicitDropPolicy = B::IMPLICIT_DROP_POLICY;
+
+	const POISON_RELEASED: bool = A::POISON_RELEASED || B::POISON_RELEASED;
+
+	#[inline]
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> B::Output {
+		A::trig_next_invalid_beh(a);
+
+		B::trig_next_invalid_beh(a)
+	}
+
+	#[inline]
+	fn trig_violation(report: &ViolationReport) -> B::Output {
+		A::trig_violation(report);
+
+		B::trig_violation(report)
+	}
+}
+
+/// Executes the trigger `Soft` for the first `N` violations (in the domain `D`)
+/// and escalates to the trigger `Hard` after that.
+///
+/// (Non-fatal, since `Soft` has to return.)
+pub struct AfterN<const N: u64, Soft, Hard, D>(PhantomData<(Soft, Hard, D)>);
+
+impl<const N: u64, Soft, Hard, D> TrigManuallyDrop for AfterN<N, Soft, Hard, D>
+where
+	Soft: TrigManuallyDrop<Output = ()>,
+	Hard: TrigManuallyDrop,
+	D: TrigDomain,
+{
+	type Output = ();
+
+	const IMPLICIT_DROP_POLICY: ImplicitDropPolicy = Hard::IMPLICIT_DROP_POLICY;
+
+	const POISON_RELEASED: bool = Soft::POISON_RELEASED || Hard::POISON_RELEASED;
+
+	#[inline]
+	fn trig_next_invalid_beh(a: Arguments<'_>) {
+		if D::state().next_violation() < N {
+			Soft::trig_next_invalid_beh(a)
+		} else {
+			Hard::trig_next_invalid_beh(a);
+		}
+	}
+
+	#[inline]
+	fn trig_violation(report: &ViolationReport) {
+		if D::state().next_violation() < N {
+			Soft::trig_violation(report)
+		} else {
+			Hard::trig_violation(report);
+		}
+	}
+}
+
+/// Executes the trigger `T` only for the first violation from each call site
+/// (in the domain `D`), the repeated violations from the same site are skipped.
+///
+/// (Non-fatal, since the repeated violations are skipped.)
+pub struct OncePerSite<T, D>(PhantomData<(T, D)>);
+
+impl<T, D> TrigManuallyDrop for OncePerSite<T, D>
+where
+	T: TrigManuallyDrop,
+	D: TrigDomain,
+{
+	type Output = ();
+
+	const IMPLICIT_DROP_POLICY: ImplicitDropPolicy = T::IMPLICIT_DROP_POLICY;
+
+	const POISON_RELEASED: bool = T::POISON_RELEASED;
+
+	/// Without a report, the call site is unknown, the repeated messages are skipped.
+	#[inline]
+	fn trig_next_invalid_beh(a: Arguments<'_>) {
+		if D::state().insert_message(a) {
+			T::trig_next_invalid_beh(a);
+		}
+	}
+
+	#[inline]
+	fn trig_violation(report: &ViolationReport) {
+		if D::state().insert_site(report.location(), report.type_name()) {
+			T::trig_violation(report);
+		}
+	}
+}
+
+#[cfg(test)]
+#[test]
+fn test_combinator_trig() {
+	use crate::core::report::ViolationKind;
+	use core::sync::atomic::AtomicUsize;
+
+	static SOFT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+	static HARD_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+	enum SoftTrig {}
+	impl TrigManuallyDrop for SoftTrig {
+		type Output = ();
+
+		fn trig_next_invalid_beh(_a: Arguments<'_>) {
+			SOFT_COUNTER.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+
+	enum HardTrig {}
+	impl TrigManuallyDrop for HardTrig {
+		type Output = ();
+
+		fn trig_next_invalid_beh(_a: Arguments<'_>) {
+			HARD_COUNTER.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+
+	enum TestDomain {}
+	impl TrigDomain for TestDomain {
+		fn state() -> &'static TrigDomainState {
+			static STATE: TrigDomainState = TrigDomainState::new();
+
+			&STATE
+		}
+	}
+
+	type Trig = AfterN<2, Chain<SoftTrig, OncePerSite<SoftTrig, TestDomain>>, HardTrig, TestDomain>;
+	let report = |location| {
+		ViolationReport::new::<()>(
+			ptr::null(),
+			crate::core::state::StateManuallyDropData::DropModeTrig,
+			crate::core::report::ManuallyDropOperation::Drop,
+			None,
+			location,
+		)
+	};
+	let site_a = Location::caller();
+	let site_b = Location::caller();
+
+	Trig::trig_violation(&report(Some(site_a))); // soft, soft (new site)
+	Trig::trig_violation(&report(Some(site_a))); // soft
+	assert_eq!(SOFT_COUNTER.load(Ordering::SeqCst), 3);
+
+	Trig::trig_violation(&report(Some(site_b))); // hard
+	assert_eq!(SOFT_COUNTER.load(Ordering::SeqCst), 3);
+	assert_eq!(HARD_COUNTER.load(Ordering::SeqCst), 1);
+	assert_eq!(TestDomain::state().violations(), 3);
+	assert_eq!(report(None).kind(), ViolationKind::DoubleDrop);
+
+	TestDomain::state().reset();
+	assert_eq!(TestDomain::state().violations(), 0);
+	assert!(TestDomain::state().insert_site(Some(site_b), "u8"));
+	assert!(!TestDomain::state().insert_site(Some(site_b), "u16"));
+
+	// the implicit drops are remembered by the type of the value
+	assert!(TestDomain::state().insert_site(None, "u8"));
+	assert!(!TestDomain::state().insert_site(None, "u8"));
+	assert!(TestDomain::state().insert_site(None, "u16"));
+
+	// without a report, the same message is passed on once
+	type OnceTrig = OncePerSite<SoftTrig, TestDomain>;
+	SOFT_COUNTER.store(0, Ordering::SeqCst);
+	OnceTrig::trig_next_invalid_beh(format_args!("violation at {}", 1));
+	OnceTrig::trig_next_invalid_beh(format_args!("violation at {}", 1));
+	OnceTrig::trig_next_invalid_beh(format_args!("violation at {}", 2));
+	assert_eq!(SOFT_COUNTER.load(Ordering::SeqCst), 2);
+
+	// the implicit drops are passed on once for each type
+	SOFT_COUNTER.store(0, Ordering::SeqCst);
+	drop(crate::beh::safe::SafeManuallyDrop::<_, OnceTrig>::new(1u32));
+	drop(crate::beh::safe::SafeManuallyDrop::<_, OnceTrig>::new(2u32));
+	drop(crate::beh::safe::SafeManuallyDrop::<_, OnceTrig>::new(3u64));
+	assert_eq!(SOFT_COUNTER.load(Ordering::SeqCst), 2);
+
+	// the implicit drop policy of the hard trigger is used
+	use crate::core::trig::policy::{LeakSilentlyPolicy, WithImplicitDropPolicy};
+	type LeakHardTrig = WithImplicitDropPolicy<HardTrig, LeakSilentlyPolicy>;
+	assert_eq!(
+		<AfterN<1, SoftTrig, LeakHardTrig, TestDomain>>::IMPLICIT_DROP_POLICY,
+		ImplicitDropPolicy::LeakSilently
+	);
+
+	// the implicit drop policy of `B` is used by the chain
+	assert_eq!(
+		<Chain<LeakHardTrig, SoftTrig>>::IMPLICIT_DROP_POLICY,
+		ImplicitDropPolicy::DropAndTrig
+	);
+	assert_eq!(
+		<Chain<SoftTrig, LeakHardTrig>>::IMPLICIT_DROP_POLICY,
+		ImplicitDropPolicy::LeakSilently
+	);
+}
@@ -1,82 +1,286 @@
+use crate::core::report::ViolationKind;
+use crate::core::report::ViolationReport;
 use crate::core::trig::TrigManuallyDrop;
 use core::fmt::Arguments;
+use core::marker::PhantomData;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
 
 mod __internal_counter_logic {
-	use core::sync::atomic::AtomicU32;
-	use core::sync::atomic::Ordering;
+	use crate::core::trig::counter::CounterDomain;
+	use crate::core::trig::counter::CounterSet;
+	use crate::core::trig::counter::GlobalCounterDomain;
+
+	static COUNT_TRIG_SAFEMANUALLYDROP: CounterSet = CounterSet::new();
+
+	impl CounterDomain for GlobalCounterDomain {
+		#[inline(always)]
+		fn counters() -> &'static CounterSet {
+			&COUNT_TRIG_SAFEMANUALLYDROP
+		}
+	}
+}
 
-	static COUNT_TRIG_SAFEMANUALLYDROP: AtomicU32 = AtomicU32::new(0);
+/// A set of counters of undefined behavior, one for each kind of violation.
+pub struct CounterSet {
+	counts: [AtomicU64; ViolationKind::COUNT],
+	/// Counter of the events without a violation report (manual +1)
+	/// or with a violation of an unknown kind.
+	other: AtomicU64,
+}
 
+impl CounterSet {
 	const DEF_SETORDERING: Ordering = Ordering::SeqCst;
 	const DEF_GETORDERING: Ordering = Ordering::Relaxed;
 
-	/// Manually add add to counter +1
+	/// Create a set of counters with zero values.
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			counts: [const { AtomicU64::new(0) }; ViolationKind::COUNT],
+			other: AtomicU64::new(0),
+		}
+	}
+
+	/// Add to the counter of the violation kind +1
+	/// (violations of an unknown kind are added to `other`).
+	#[inline]
+	pub fn add(&self, kind: ViolationKind) {
+		match kind {
+			ViolationKind::Unknown => self.add_other(),
+			kind => {
+				self.counts[kind.index()].fetch_add(1, Self::DEF_SETORDERING);
+			}
+		}
+	}
+
+	/// Manually add add to counter +1 (an event without a violation report).
 	#[inline]
-	pub unsafe fn trig_next_invalid_beh() {
-		COUNT_TRIG_SAFEMANUALLYDROP.fetch_add(1, DEF_SETORDERING);
+	pub fn add_other(&self) {
+		self.other.fetch_add(1, Self::DEF_SETORDERING);
+	}
+
+	/// Read all counters, replacing each of them with the result of the function.
+	#[inline]
+	fn map_counters(&self, mut next: impl FnMut(&AtomicU64) -> u64) -> CounterSnapshot {
+		let mut counts = [0; ViolationKind::COUNT];
+		for (count, counter) in counts.iter_mut().zip(self.counts.iter()) {
+			*count = next(counter);
+		}
+
+		CounterSnapshot {
+			counts,
+			other: next(&self.other),
+		}
 	}
 
 	/// Get the number of times the undefined behavior was triggered.
 	#[inline]
-	pub fn get_count_trig_events() -> u32 {
-		COUNT_TRIG_SAFEMANUALLYDROP.load(DEF_GETORDERING)
+	pub fn get_count_trig_events(&self) -> u64 {
+		self.snapshot().total()
+	}
+
+	/// Get the values of all counters.
+	#[inline]
+	pub fn snapshot(&self) -> CounterSnapshot {
+		self.map_counters(|counter| counter.load(Self::DEF_GETORDERING))
+	}
+
+	/// Reset all counters to zero.
+	#[inline]
+	pub fn reset(&self) {
+		let _e = self.swap_and_reset();
+	}
+
+	/// Reset all counters to zero, returning their values
+	/// (for example, to export the deltas on each metrics scrape).
+	#[inline]
+	pub fn swap_and_reset(&self) -> CounterSnapshot {
+		self.map_counters(|counter| counter.swap(0, Self::DEF_SETORDERING))
 	}
 }
 
+impl Default for CounterSet {
+	#[inline(always)]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A domain of counters, each domain has its own static set of counters,
+/// so that subsystems (or tests running in parallel) do not share counters.
+///
+/// ```rust
+/// use SafeManuallyDrop::core::trig::counter::{CounterDomain, CounterSet};
+/// use SafeManuallyDrop::core::trig::counter::AlwaysSafeCounterManuallyDrop;
+///
+/// enum MyPoolDomain {}
+///
+/// impl CounterDomain for MyPoolDomain {
+/// 	fn counters() -> &'static CounterSet {
+/// 		static COUNTERS: CounterSet = CounterSet::new();
+///
+/// 		&COUNTERS
+/// 	}
+/// }
+///
+/// let data = AlwaysSafeCounterManuallyDrop::<_, MyPoolDomain>::new(1);
+/// assert_eq!(MyPoolDomain::counters().get_count_trig_events(), 0);
+/// # drop(data);
+/// ```
+pub trait CounterDomain {
+	/// The set of counters of the domain.
+	fn counters() -> &'static CounterSet;
+}
+
+/// The global domain of counters, used by default.
+pub enum GlobalCounterDomain {}
+
 /// A protected version of SafeManuallyDrop with a function to count the amount of undefined behavior of the ManuallyDrop logic.
-/// The undefined behavior of CounterManuallyDrop will be the same as when using the standard ManuallyDrop.
-pub type AlwaysSafeCounterManuallyDrop<T> =
-	crate::beh::safe::SafeManuallyDrop<T, CounterTrigManuallyDrop>;
+/// After counting, the operation is skipped (drop) or refused with a panic (take, deref,
+/// replace, lend, into_inner), so the undefined behavior does not happen.
+pub type AlwaysSafeCounterManuallyDrop<T, D = GlobalCounterDomain> =
+	crate::beh::safe::SafeManuallyDrop<T, CounterTrigManuallyDrop<D>>;
 
 /// A secure or non-secure version of SafeManuallyDrop with a
 /// function to count the undefined behavior of the ManuallyDrop logic.
-/// The undefined behavior of CounterManuallyDrop will be the same as when
-/// using the standard ManuallyDrop.
-pub type AutoSafeCounterManuallyDrop<T> =
-	crate::beh::auto::AutoSafeManuallyDrop<T, CounterTrigManuallyDrop>;
+/// In the secure version, the operation is skipped or refused after counting
+/// (see `AlwaysSafeCounterManuallyDrop`).
+pub type AutoSafeCounterManuallyDrop<T, D = GlobalCounterDomain> =
+	crate::beh::auto::AutoSafeManuallyDrop<T, CounterTrigManuallyDrop<D>>;
 
-/// On undefined behavior, ManuallyDrop enables the undefined behavior,
-/// but increments the global counter by +1 each time it detects undefined behavior.
-pub enum CounterTrigManuallyDrop {}
+/// On undefined behavior, increments the counter of the violation kind (in the domain `D`) by +1,
+/// then the operation is skipped (drop) or refused with a panic (take, deref, replace, lend,
+/// into_inner), the undefined behavior itself does not happen.
+pub struct CounterTrigManuallyDrop<D = GlobalCounterDomain>(PhantomData<D>);
+
+impl<D: CounterDomain> TrigManuallyDrop for CounterTrigManuallyDrop<D> {
+	type Output = ();
 
-impl TrigManuallyDrop for CounterTrigManuallyDrop {
 	// #[inline(always)] ignore!,
 	#[inline]
-	fn trig_next_invalid_beh(_a: Arguments<'_>) -> trig_manuallydrop_returntype!() {
-		unsafe { crate::core::trig::counter::trig_next_invalid_beh() }
+	fn trig_next_invalid_beh(_a: Arguments<'_>) {
+		D::counters().add_other()
+	}
+
+	#[inline]
+	fn trig_violation(report: &ViolationReport) {
+		D::counters().add(report.kind())
+	}
+}
+
+/// Values of the counters of undefined behavior, for each kind of violation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CounterSnapshot {
+	counts: [u64; ViolationKind::COUNT],
+	other: u64,
+}
+
+impl CounterSnapshot {
+	/// The number of violations of the given kind
+	/// (for `ViolationKind::Unknown`, the same as `other`).
+	#[inline]
+	pub const fn get(&self, kind: ViolationKind) -> u64 {
+		match kind {
+			ViolationKind::Unknown => self.other,
+			kind => self.counts[kind.index()],
+		}
+	}
+
+	/// The number of events added without a violation report (manual +1)
+	/// or with a violation of an unknown kind.
+	#[inline]
+	pub const fn other(&self) -> u64 {
+		self.other
+	}
+
+	/// The total number of events.
+	#[inline]
+	pub fn total(&self) -> u64 {
+		self.counts.iter().sum::<u64>() + self.other
+	}
+
+	/// Iterate over the violation kinds and their counts (without `Unknown`, see `other`).
+	#[inline]
+	pub fn iter(&self) -> impl Iterator<Item = (ViolationKind, u64)> + '_ {
+		ViolationKind::ALL
+			.iter()
+			.filter(|kind| **kind != ViolationKind::Unknown)
+			.map(move |kind| (*kind, self.get(*kind)))
 	}
 }
 
 /// Manually add add to counter +1
+///
+/// # Safety
+///
+/// The caller continues after the violation, the operation that caused it must be skipped.
 #[inline]
 pub unsafe fn trig_next_invalid_beh() {
-	__internal_counter_logic::trig_next_invalid_beh();
+	GlobalCounterDomain::counters().add_other();
 }
 
 /// Get the number of times the undefined behavior was triggered.
 #[inline]
-pub fn get_count_trig_events() -> u32 {
-	__internal_counter_logic::get_count_trig_events()
+pub fn get_count_trig_events() -> u64 {
+	GlobalCounterDomain::counters().get_count_trig_events()
 }
 
-impl AutoSafeCounterManuallyDrop<()> {
-	/// Get the number of times the undefined behavior was triggered.
+/// Get the values of the counters for each kind of violation.
+#[inline]
+pub fn snapshot() -> CounterSnapshot {
+	GlobalCounterDomain::counters().snapshot()
+}
+
+/// Reset all counters to zero.
+#[inline]
+pub fn reset() {
+	GlobalCounterDomain::counters().reset()
+}
+
+/// Reset all counters to zero, returning their values
+/// (for example, to export the deltas on each metrics scrape).
+#[inline]
+pub fn swap_and_reset() -> CounterSnapshot {
+	GlobalCounterDomain::counters().swap_and_reset()
+}
+
+impl<D: CounterDomain> AutoSafeCounterManuallyDrop<(), D> {
+	/// Get the number of times the undefined behavior was triggered (in the domain `D`).
 	#[inline(always)]
-	pub fn get_count_trig_events() -> u32 {
-		crate::core::trig::counter::get_count_trig_events()
+	pub fn get_count_trig_events() -> u64 {
+		D::counters().get_count_trig_events()
+	}
+
+	/// Get the values of the counters for each kind of violation (in the domain `D`).
+	#[inline(always)]
+	pub fn counter_snapshot() -> CounterSnapshot {
+		D::counters().snapshot()
 	}
 }
 
 #[cfg(test)]
 #[test]
 fn test_counter_trig_manuallydrop() {
-	use core::sync::atomic::AtomicU32;
+	use core::sync::atomic::AtomicU64;
 	use core::sync::atomic::Ordering;
 
 	const DEF_SETORDERING: Ordering = Ordering::SeqCst;
 	const DEF_GETORDERING: Ordering = Ordering::Relaxed;
 
-	static __TEST_COUNTER: AtomicU32 = AtomicU32::new(0);
+	enum __TestDomain {}
+	impl CounterDomain for __TestDomain {
+		fn counters() -> &'static CounterSet {
+			static __TEST_COUNTERS: CounterSet = CounterSet::new();
+
+			&__TEST_COUNTERS
+		}
+	}
+	type AlwaysSafeCounterManuallyDrop<T> =
+		crate::core::trig::counter::AlwaysSafeCounterManuallyDrop<T, __TestDomain>;
+	let counters = __TestDomain::counters();
+
+	static __TEST_COUNTER: AtomicU64 = AtomicU64::new(0);
 	struct __Test;
 	impl Drop for __Test {
 		#[inline]
@@ -93,14 +297,33 @@ fn test_counter_trig_manuallydrop() {
 		assert_eq!(AlwaysSafeCounterManuallyDrop::get_count_trig_events(), 0);
 
 		AlwaysSafeCounterManuallyDrop::drop(&mut check_data);
-		assert_eq!(AlwaysSafeCounterManuallyDrop::get_count_trig_events(), 1);
+		assert_eq!(counters.get_count_trig_events(), 1);
 
 		AlwaysSafeCounterManuallyDrop::drop(&mut check_data);
-		assert_eq!(AlwaysSafeCounterManuallyDrop::get_count_trig_events(), 2);
+		assert_eq!(counters.get_count_trig_events(), 2);
 
-		assert_eq!(
-			__TEST_COUNTER.load(DEF_GETORDERING),
-			AlwaysSafeCounterManuallyDrop::get_count_trig_events() + 1
-		);
+		// the repeated drops are skipped (recovery mode)
+		assert_eq!(__TEST_COUNTER.load(DEF_GETORDERING), 1);
 	}
+
+	// take after drop
+	assert!(AlwaysSafeCounterManuallyDrop::take_or_none(&mut check_data).is_none());
+	assert_eq!(__TEST_COUNTER.load(DEF_GETORDERING), 1);
+
+	let snapshot = AlwaysSafeCounterManuallyDrop::counter_snapshot();
+	assert_eq!(snapshot.get(ViolationKind::DoubleDrop), 2);
+	assert_eq!(snapshot.get(ViolationKind::TakeAfterDrop), 1);
+	assert_eq!(snapshot.get(ViolationKind::DoubleTake), 0);
+	assert_eq!(snapshot.total(), 3);
+
+	// a violation of an unknown kind is counted as other
+	counters.add(ViolationKind::Unknown);
+	let snapshot = counters.snapshot();
+	assert_eq!(snapshot.other(), 1);
+	assert_eq!(snapshot.get(ViolationKind::Unknown), 1);
+	assert_eq!(snapshot.total(), 4);
+
+	assert_eq!(counters.swap_and_reset(), snapshot);
+	assert_eq!(counters.snapshot(), CounterSnapshot::default());
+	assert_eq!(counters.get_count_trig_events(), 0);
 }
@@ -1,5 +1,19 @@
+use crate::core::report::ViolationReport;
 use crate::core::trig::TrigManuallyDrop;
+use crate::core::trig::TrigOutput;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::convert::Infallible;
 use core::fmt::Arguments;
+use core::ptr;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::Ordering;
+#[cfg(feature = "std")]
+use std::cell::Cell;
 
 /// Protected version of the SafeManuallyDrop with an execution
 /// function in case of undefined behavior of the ManuallyDrop logic.
@@ -13,51 +27,410 @@ pub type AutoSafeHookManuallyDrop<T> =
 
 /// The hook function to be executed in case of
 /// undefined behavior of the HookManuallyDrop.
-pub type HookFunction = fn(Arguments) -> trig_manuallydrop_returntype!();
+pub type HookFunction = fn(Arguments) -> !;
+
+/// The hook function with a user context (does not require allocation), the context
+/// pointer passed to `set_hook_with_context` is passed as the first argument.
+pub type ContextHookFunction = fn(*const (), Arguments) -> !;
+
+/// The hook closure with a captured state.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub type BoxedHookFunction = Box<dyn Fn(&ViolationReport) + Send + Sync>;
 
 /// In case of undefined behavior of manual memory management, execute an external hook.
 pub enum HookFnTrigManuallyDrop {}
 
+/// Atomic storage of a hook function, the global hook is stored in it,
+/// and it can also be used to save the previous hook in order to chain to it.
+///
+/// ```rust
+/// use SafeManuallyDrop::core::trig::hook::{self, AtomicHook};
+///
+/// static PREV_HOOK: AtomicHook = AtomicHook::new();
+///
+/// PREV_HOOK.set(hook::set_hook(|args| {
+/// 	println!("ManuallyDrop: {}", args);
+///
+/// 	PREV_HOOK.run(args)
+/// }));
+/// ```
+pub struct AtomicHook {
+	/// HookFunction, null is the default hook.
+	hook: AtomicPtr<()>,
+}
+
+impl AtomicHook {
+	const SETORDERING: Ordering = Ordering::SeqCst;
+	const GETORDERING: Ordering = Ordering::SeqCst;
+
+	/// Create a storage with the default hook (panic).
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			hook: AtomicPtr::new(ptr::null_mut()),
+		}
+	}
+
+	/// Convert a raw pointer from the storage to a hook function.
+	#[inline(always)]
+	fn __from_ptr(hook: *mut ()) -> HookFunction {
+		if hook.is_null() {
+			return default_hook;
+		}
+
+		// safe, only HookFunction is written to the pointer.
+		unsafe { core::mem::transmute::<*mut (), HookFunction>(hook) }
+	}
+
+	/// Set a hook function, the previous hook function is returned.
+	#[inline]
+	pub fn set(&self, function: HookFunction) -> HookFunction {
+		let old_hook = self.hook.swap(function as *mut (), Self::SETORDERING);
+
+		Self::__from_ptr(old_hook)
+	}
+
+	/// Reset the hook function to the default one (panic),
+	/// the previous hook function is returned.
+	#[inline]
+	pub fn take(&self) -> HookFunction {
+		let old_hook = self.hook.swap(ptr::null_mut(), Self::SETORDERING);
+
+		Self::__from_ptr(old_hook)
+	}
+
+	/// Get the current hook function.
+	#[inline]
+	pub fn get(&self) -> HookFunction {
+		Self::__from_ptr(self.hook.load(Self::GETORDERING))
+	}
+
+	/// Execute the current hook function.
+	#[inline]
+	pub fn run(&self, args: Arguments<'_>) -> ! {
+		let trig_fn = self.get();
+
+		trig_fn(args)
+	}
+}
+
+impl Default for AtomicHook {
+	#[inline(always)]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 /// Hook is a function to be executed in case of
 /// undefined behavior of the ManuallyDrop logic.
-static mut HOOK: HookFunction = __cold_panic;
+static HOOK: AtomicHook = AtomicHook::new();
 
-/// for internal use only, the same panic but cold.
+/// The default hook, the same panic but cold.
 #[cold]
 #[inline(never)]
-fn __cold_panic(args: Arguments<'_>) -> trig_manuallydrop_returntype!() {
+pub fn default_hook(args: Arguments<'_>) -> ! {
 	panic!("{}", args);
 }
 
-// UNSAFE_MODE TODO!!
 /// Set a hook function to be executed in case of
-/// undefined behavior of ManuallyDrop logic.
-#[inline(always)]
-pub unsafe fn set_hook(function: HookFunction) {
-	HOOK = function;
+/// undefined behavior of ManuallyDrop logic, the previous hook function is returned.
+///
+/// The extended hook (`set_hook_with_context`, `set_hook_boxed`) takes precedence
+/// over the hook function, so it is unregistered (use `take_extended_hook` first
+/// to keep it).
+#[inline]
+pub fn set_hook(function: HookFunction) -> HookFunction {
+	let _extended_hook = take_extended_hook();
+
+	HOOK.set(function)
+}
+
+/// Unregister the current hook function and the extended hook
+/// (the default hook will be executed, panic) and return the hook function.
+#[inline]
+pub fn take_hook() -> HookFunction {
+	let _extended_hook = take_extended_hook();
+
+	HOOK.take()
 }
 
 /// Get a hook function that will be executed in case of
 /// undefined behavior of the ManuallyDrop logic.
 #[inline(always)]
-pub fn take_hook() -> HookFunction {
-	// TODO UNSAFE MODE
-	unsafe { HOOK }
+pub fn get_hook() -> HookFunction {
+	HOOK.get()
+}
+
+/// A hook with a state (a context pointer or a captured closure),
+/// takes precedence over the hook function (`set_hook`).
+#[derive(Clone)]
+pub enum ExtendedHook {
+	/// The hook function with a user context (`set_hook_with_context`).
+	Context {
+		/// The hook function.
+		function: ContextHookFunction,
+		/// The context passed to the hook function (`&'static C`).
+		context: *const (),
+	},
+
+	/// The hook closure with a captured state (`set_hook_boxed`).
+	#[cfg(feature = "alloc")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+	Boxed(Arc<dyn Fn(&ViolationReport) + Send + Sync>),
+}
+
+// The context pointer is always obtained from `&'static C` where `C: Sync`,
+// the closure is always `Send + Sync`.
+unsafe impl Send for ExtendedHook {}
+unsafe impl Sync for ExtendedHook {}
+
+impl ExtendedHook {
+	/// Execute the hook.
+	///
+	/// If the closure hook returns, the default hook is executed (panic), since
+	/// the operation cannot be continued.
+	pub fn run(&self, report: &ViolationReport) -> ! {
+		match self {
+			Self::Context { function, context } => function(*context, format_args!("{}", report)),
+
+			#[cfg(feature = "alloc")]
+			Self::Boxed(function) => {
+				function(report);
+
+				default_hook(format_args!("{}", report))
+			}
+		}
+	}
+
+	/// Execute the hook for a violation reported with only a message
+	/// (the closure hook receives `ViolationReport::unknown`).
+	///
+	/// If the closure hook returns, the default hook is executed (panic), since
+	/// the operation cannot be continued.
+	pub fn run_args(&self, args: Arguments<'_>) -> ! {
+		match self {
+			Self::Context { function, context } => function(*context, args),
+
+			#[cfg(feature = "alloc")]
+			Self::Boxed(function) => {
+				function(&ViolationReport::unknown(args));
+
+				default_hook(args)
+			}
+		}
+	}
+}
+
+/// Slot of the global extended hook, protected by a spin lock
+/// (the lock is only held to copy or replace the hook, never while it is executed).
+struct ExtendedHookSlot {
+	lock: AtomicBool,
+	hook: UnsafeCell<Option<ExtendedHook>>,
+}
+
+// The hook is only accessed under the lock.
+unsafe impl Sync for ExtendedHookSlot {}
+
+impl ExtendedHookSlot {
+	#[inline]
+	const fn new() -> Self {
+		Self {
+			lock: AtomicBool::new(false),
+			hook: UnsafeCell::new(None),
+		}
+	}
+
+	/// Execute a function with exclusive access to the hook.
+	fn with<R>(&self, next: impl FnOnce(&mut Option<ExtendedHook>) -> R) -> R {
+		/// Unlocks the slot, also on unwind.
+		struct Unlock<'a>(&'a AtomicBool);
+
+		impl Drop for Unlock<'_> {
+			#[inline]
+			fn drop(&mut self) {
+				self.0.store(false, Ordering::Release);
+			}
+		}
+
+		while self
+			.lock
+			.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+			.is_err()
+		{
+			core::hint::spin_loop();
+		}
+		let _unlock = Unlock(&self.lock);
+
+		next(unsafe { &mut *self.hook.get() })
+	}
+}
+
+/// The global extended hook (context or closure).
+static EXTENDED_HOOK: ExtendedHookSlot = ExtendedHookSlot::new();
+
+/// Set a hook function with a user context (for no_std, does not require allocation),
+/// the previous extended hook is returned.
+///
+/// The extended hook takes precedence over the hook function (`set_hook`).
+///
+/// ```rust
+/// use SafeManuallyDrop::core::trig::hook;
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static COUNTER: AtomicUsize = AtomicUsize::new(0);
+///
+/// hook::set_hook_with_context(
+/// 	|context, args| {
+/// 		let counter = unsafe { &*(context as *const AtomicUsize) };
+/// 		counter.fetch_add(1, Ordering::SeqCst);
+///
+/// 		panic!("{}", args);
+/// 	},
+/// 	&COUNTER,
+/// );
+/// # hook::take_extended_hook();
+/// ```
+pub fn set_hook_with_context<C: Sync>(
+	function: ContextHookFunction,
+	context: &'static C,
+) -> Option<ExtendedHook> {
+	let new_hook = ExtendedHook::Context {
+		function,
+		context: context as *const C as *const (),
+	};
+
+	EXTENDED_HOOK.with(|hook| hook.replace(new_hook))
+}
+
+/// Set a hook closure with a captured state, the previous extended hook is returned
+/// (it can be captured by the new closure to chain to it).
+///
+/// The extended hook takes precedence over the hook function (`set_hook`).
+/// If the closure returns, the default hook is executed (panic), since
+/// the operation cannot be continued.
+///
+/// ```rust
+/// use SafeManuallyDrop::core::trig::hook;
+/// use std::sync::mpsc;
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let sender = std::sync::Mutex::new(sender);
+///
+/// hook::set_hook_boxed(Box::new(move |report| {
+/// 	let _e = sender.lock().unwrap().send(report.kind());
+/// }));
+/// # hook::take_extended_hook();
+/// # drop(receiver);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub fn set_hook_boxed(function: BoxedHookFunction) -> Option<ExtendedHook> {
+	let new_hook = ExtendedHook::Boxed(Arc::from(function));
+
+	EXTENDED_HOOK.with(|hook| hook.replace(new_hook))
+}
+
+/// Unregister the current extended hook (the hook function will be executed)
+/// and return it.
+#[inline]
+pub fn take_extended_hook() -> Option<ExtendedHook> {
+	EXTENDED_HOOK.with(|hook| hook.take())
+}
+
+/// Get the current extended hook.
+#[inline]
+pub fn get_extended_hook() -> Option<ExtendedHook> {
+	EXTENDED_HOOK.with(|hook| hook.clone())
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+	/// Hook of the current thread installed by `with_hook`, takes precedence over the global hook.
+	static THREAD_HOOK: Cell<Option<HookFunction>> = const { Cell::new(None) };
+}
+
+/// Execute `body` with a hook function installed only for the current thread,
+/// the previous hook of the thread is restored afterwards (including on unwind).
+///
+/// Other threads (for example, other tests running in parallel) continue to use
+/// the global hook, so the hook cannot steal their violations.
+///
+/// ```rust
+/// use SafeManuallyDrop::core::trig::hook;
+///
+/// let result = hook::with_hook(
+/// 	|args| panic!("scoped hook: {}", args),
+/// 	|| {
+/// 		// In case of undefined behavior of the ManuallyDrop logic
+/// 		// in the current thread, the scoped hook is executed.
+/// 		1 + 1
+/// 	},
+/// );
+/// assert_eq!(result, 2);
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn with_hook<R>(function: HookFunction, body: impl FnOnce() -> R) -> R {
+	/// Restores the previous hook of the thread, also on unwind.
+	struct RestoreHook(Option<HookFunction>);
+
+	impl Drop for RestoreHook {
+		#[inline]
+		fn drop(&mut self) {
+			let _e = THREAD_HOOK.try_with(|hook| hook.set(self.0));
+		}
+	}
+
+	let old_hook = THREAD_HOOK.with(|hook| hook.replace(Some(function)));
+	let _restore = RestoreHook(old_hook);
+
+	body()
 }
 
 /// Execute a hook function that is always executed in case of
-/// undefined behavior of the ManuallyDrop logic.
-#[inline(always)]
-pub fn run_hook(args: Arguments<'_>) -> trig_manuallydrop_returntype!() {
-	let trig_fn = take_hook();
+/// undefined behavior of the ManuallyDrop logic: the hook of the current thread
+/// (`with_hook`), otherwise the extended hook (context or closure, the closure
+/// receives `ViolationReport::unknown`), otherwise the hook function.
+#[inline]
+pub fn run_hook(args: Arguments<'_>) -> ! {
+	#[cfg(feature = "std")]
+	if let Ok(Some(trig_fn)) = THREAD_HOOK.try_with(Cell::get) {
+		trig_fn(args);
+	}
 
-	trig_fn(args)
+	match get_extended_hook() {
+		Some(extended_hook) => extended_hook.run_args(args),
+		None => HOOK.run(args),
+	}
+}
+
+/// Execute the hook for the violation report: the hook of the current thread
+/// (`with_hook`), otherwise the extended hook (context or closure),
+/// otherwise the hook function.
+pub fn run_hook_report(report: &ViolationReport) -> ! {
+	#[cfg(feature = "std")]
+	if let Ok(Some(trig_fn)) = THREAD_HOOK.try_with(Cell::get) {
+		trig_fn(format_args!("{}", report));
+	}
+
+	match get_extended_hook() {
+		Some(extended_hook) => extended_hook.run(report),
+		None => HOOK.run(format_args!("{}", report)),
+	}
 }
 
 impl TrigManuallyDrop for HookFnTrigManuallyDrop {
+	type Output = Infallible;
+
 	#[inline(always)]
-	fn trig_next_invalid_beh(a: Arguments<'_>) -> trig_manuallydrop_returntype!() {
-		crate::core::trig::hook::run_hook(a);
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> Infallible {
+		crate::core::trig::hook::run_hook(a)
+	}
+
+	#[inline(always)]
+	fn trig_violation(report: &ViolationReport) -> Infallible {
+		crate::core::trig::hook::run_hook_report(report)
 	}
 }
 
@@ -68,17 +441,22 @@ impl TrigManuallyDrop for HookFnTrigManuallyDrop {
 /// an empty struct as its type, give the struct the traits Default and FnOnce.
 /// Works only in a nightlight using certain flags.
 ///
+/// A function that returns `Infallible` is a fatal trigger,
+/// a function that returns `()` is a non-fatal trigger.
+///
 /// TODO, Exp support <https://github.com/rust-lang/rust/issues/35121>
-impl<F> TrigManuallyDrop for F
+impl<F, O> TrigManuallyDrop for F
 where
-	F: Default + FnOnce(Arguments<'_>),
+	F: Default + FnOnce(Arguments<'_>) -> O,
+	O: TrigOutput,
 {
+	type Output = O;
+
 	#[inline]
-	fn trig_next_invalid_beh(a: Arguments<'_>) -> trig_manuallydrop_returntype!() {
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> O {
 		let function: F = Default::default();
 
-		function(a); // <-- exp return: !
-		__cold_panic(format_args!("Be sure to read the documentation an anonymous function should abort a thread, but it's not possible to do this in safe rust at the moment."));
+		function(a)
 	}
 }
 /*#[cfg(test)]
@@ -98,23 +476,53 @@ fn test_anonym_hook() {
 
 impl AutoSafeHookManuallyDrop<()> {
 	/// Set a hook function to be executed in case of undefined behavior
-	/// of ManuallyDrop logic.
+	/// of ManuallyDrop logic, the previous hook function is returned.
 	#[inline(always)]
-	pub unsafe fn set_hook(function: HookFunction) {
+	pub fn set_hook(function: HookFunction) -> HookFunction {
 		crate::core::trig::hook::set_hook(function)
 	}
 
-	/// Get a hook function that will be executed in case of undefined behavior
-	/// of the ManuallyDrop logic.
+	/// Unregister the current hook function (the default hook will be executed,
+	/// panic) and return it.
 	#[inline(always)]
 	pub fn take_hook() -> HookFunction {
 		crate::core::trig::hook::take_hook()
 	}
 
+	/// Get a hook function that will be executed in case of undefined behavior
+	/// of the ManuallyDrop logic.
+	#[inline(always)]
+	pub fn get_hook() -> HookFunction {
+		crate::core::trig::hook::get_hook()
+	}
+
 	/// Execute a hook function that is always executed in case of undefined behavior
 	/// of the ManuallyDrop logic.
 	#[inline(always)]
-	pub fn run_hook(args: Arguments<'_>) -> trig_manuallydrop_returntype!() {
+	pub fn run_hook(args: Arguments<'_>) -> ! {
 		crate::core::trig::hook::run_hook(args)
 	}
 }
+
+#[cfg(test)]
+#[test]
+fn test_set_take_hook() {
+	fn hook1(args: Arguments<'_>) -> ! {
+		default_hook(args)
+	}
+	fn hook2(args: Arguments<'_>) -> ! {
+		default_hook(format_args!("hook2: {}", args))
+	}
+
+	let addr = |f: HookFunction| f as *const ();
+
+	let storage = AtomicHook::new();
+	assert_eq!(addr(storage.get()), addr(default_hook));
+
+	assert_eq!(addr(storage.set(hook1)), addr(default_hook));
+	assert_eq!(addr(storage.set(hook2)), addr(hook1));
+	assert_eq!(addr(storage.get()), addr(hook2));
+
+	assert_eq!(addr(storage.take()), addr(hook2));
+	assert_eq!(addr(storage.get()), addr(default_hook));
+}
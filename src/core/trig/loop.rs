@@ -1,4 +1,5 @@
 use crate::core::trig::TrigManuallyDrop;
+use core::convert::Infallible;
 use core::fmt::Arguments;
 
 #[cfg(feature = "support_istrig_loop")]
@@ -31,7 +32,9 @@ pub type AutoSafeEmptyLoopManuallyDrop<T> =
 pub enum EmptyLoopTrigManuallyDrop {}
 
 impl TrigManuallyDrop for EmptyLoopTrigManuallyDrop {
-	fn trig_next_invalid_beh(_a: Arguments<'_>) -> trig_manuallydrop_returntype!() {
+	type Output = Infallible;
+
+	fn trig_next_invalid_beh(_a: Arguments<'_>) -> Infallible {
 		#[cfg(feature = "support_istrig_loop")]
 		unsafe {
 			crate::core::trig::r#loop::trig_next_invalid_beh();
@@ -40,7 +43,7 @@ impl TrigManuallyDrop for EmptyLoopTrigManuallyDrop {
 		#[allow(clippy::empty_loop)]
 		#[inline(never)]
 		#[cold]
-		fn _cold_loop() -> trig_manuallydrop_returntype!() {
+		fn _cold_loop() -> ! {
 			loop {}
 		}
 
@@ -50,6 +53,10 @@ impl TrigManuallyDrop for EmptyLoopTrigManuallyDrop {
 
 /// Globally marks the presence state of a
 /// looped thread when undefined behavior is detected.
+///
+/// # Safety
+///
+/// The caller continues after the violation, the operation that caused it must be skipped.
 #[cfg(feature = "support_istrig_loop")]
 #[inline]
 pub unsafe fn trig_next_invalid_beh() {
@@ -59,6 +66,10 @@ pub unsafe fn trig_next_invalid_beh() {
 
 /// Globally marks the presence state of a
 /// looped thread when undefined behavior is detected.
+///
+/// # Safety
+///
+/// The caller continues after the violation, the operation that caused it must be skipped.
 #[cfg(not(feature = "support_istrig_loop"))]
 #[inline]
 pub const unsafe fn trig_next_invalid_beh() {}
@@ -1,29 +1,8 @@
+use crate::core::report::ViolationReport;
+use crate::core::trig::policy::ImplicitDropPolicy;
+use core::convert::Infallible;
 use core::fmt::Arguments;
 
-/// Depending on the configuration, determines the return type of the trigger (! or ()).
-///
-/// ((there are types that require ! for example panic, and there are counter types that require ()))
-// TODO! Wait stable https://github.com/rust-lang/rust/issues/35121
-#[cfg(not(feature = "support_count_trig"))]
-macro_rules! trig_manuallydrop_returntype {
-	[] => {
-		!
-	};
-}
-
-/// Depending on the configuration, determines the return type of the trigger (! or ()).
-///
-/// ((there are types that require ! for example panic, and there are counter types that require ()))
-// TODO! Wait stable https://github.com/rust-lang/rust/issues/35121
-#[cfg(feature = "support_count_trig")]
-macro_rules! trig_manuallydrop_returntype {
-	[] => {
-		()
-	};
-}
-
-pub(crate) use trig_manuallydrop_returntype;
-
 /// A protected version of SafeManuallyDrop with a function to execute a panic
 /// in case of undefined behavior of the ManuallyDrop logic.
 #[cfg(feature = "support_panic_trig")]
@@ -38,8 +17,8 @@ pub mod abort;
 
 /// A protected version of SafeManuallyDrop with a function to count
 /// the amount of undefined behavior of the ManuallyDrop logic.
-/// The undefined behavior of CounterManuallyDrop will be the same as
-/// when using the standard ManuallyDrop.
+/// After counting, the operation is skipped (drop) or refused with a panic (take, deref,
+/// replace, lend, into_inner), so the undefined behavior does not happen.
 #[cfg(feature = "support_count_trig")]
 #[cfg_attr(docsrs, doc(cfg(feature = "support_count_trig")))]
 pub mod counter;
@@ -93,10 +72,91 @@ pub(crate) use current_deftrig::BUILD_FLAG_INVALID_AUTO_DETECT_DEFTRIG_ENABLED;
 /// thread looped.
 pub mod r#loop;
 
+/// Triggers built from other triggers: run one after another (`Chain`),
+/// escalate after a threshold (`AfterN`) and deduplicate by call site (`OncePerSite`).
+pub mod combinator;
+
+/// A protected version of SafeManuallyDrop that records the last violations
+/// of the ManuallyDrop logic in a lock-free ring buffer and continues.
+pub mod record;
+
+/// What happens to the value when the safe ManuallyDrop is dropped without releasing it
+/// (drop or leak the value, execute the trigger or not).
+pub mod policy;
+
+mod sealed {
+	pub trait Sealed {}
+
+	impl Sealed for core::convert::Infallible {}
+	impl Sealed for () {}
+}
+
+/// The result type of the trigger: `Infallible` for fatal triggers (the trigger never
+/// returns, the operation is not continued) and `()` for non-fatal triggers
+/// (the trigger returns, for example, after incrementing a counter).
+pub trait TrigOutput: sealed::Sealed {
+	/// Whether the trigger never returns.
+	const IS_FATAL: bool;
+}
+
+impl TrigOutput for Infallible {
+	const IS_FATAL: bool = true;
+}
+
+impl TrigOutput for () {
+	const IS_FATAL: bool = false;
+}
+
 /// Implementation of behavior in case of detection of
 /// undefined manual memory management.
 pub trait TrigManuallyDrop {
+	/// The result type of the trigger, `Infallible` for fatal triggers
+	/// (panic, abort, loop, hook) and `()` for non-fatal triggers (counter).
+	type Output: TrigOutput;
+
+	/// What happens to the value when the safe ManuallyDrop is dropped without
+	/// releasing it (by default, the value is dropped and the trigger is executed).
+	const IMPLICIT_DROP_POLICY: ImplicitDropPolicy = ImplicitDropPolicy::DropAndTrig;
+
+	/// Whether the memory of a released value is poisoned (by default it is not),
+	/// a released ManuallyDrop of such a type must not be moved until reinit
+	/// (see `core::poison::WithPoisonFill`).
+	const POISON_RELEASED: bool = false;
+
 	/// Implementation of behavior in case of detection of
 	/// undefined manual memory management.
-	fn trig_next_invalid_beh(a: Arguments<'_>) -> trig_manuallydrop_returntype!();
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> Self::Output;
+
+	/// Implementation of behavior in case of detection of
+	/// undefined manual memory management, with a structured description
+	/// of the violation.
+	///
+	/// By default, the report is formatted and passed to `trig_next_invalid_beh`.
+	#[inline]
+	fn trig_violation(report: &ViolationReport) -> Self::Output {
+		Self::trig_next_invalid_beh(format_args!("{}", report))
+	}
+}
+
+/// A trigger that never returns (panic, abort, loop, hook), the code guarded by
+/// such a trigger is never continued after undefined behavior is detected,
+/// regardless of the enabled features.
+pub trait FatalTrigManuallyDrop: TrigManuallyDrop<Output = Infallible> {}
+
+impl<Trig: TrigManuallyDrop<Output = Infallible>> FatalTrigManuallyDrop for Trig {}
+
+#[cfg(test)]
+#[test]
+fn test_trig_output() {
+	fn is_fatal<Trig: TrigManuallyDrop>() -> bool {
+		<Trig::Output as TrigOutput>::IS_FATAL
+	}
+
+	assert!(is_fatal::<r#loop::EmptyLoopTrigManuallyDrop>());
+	#[cfg(feature = "support_panic_trig")]
+	assert!(is_fatal::<panic::PanicTrigManuallyDrop>());
+	#[cfg(feature = "support_hookfn_trig")]
+	assert!(is_fatal::<hook::HookFnTrigManuallyDrop>());
+	#[cfg(feature = "support_count_trig")]
+	assert!(!is_fatal::<counter::CounterTrigManuallyDrop>());
 }
@@ -1,4 +1,5 @@
 use crate::core::trig::TrigManuallyDrop;
+use core::convert::Infallible;
 use core::fmt::Arguments;
 
 /// A protected version of ManuallyDrop with a function to
@@ -16,10 +17,12 @@ pub type AutoSafePanicManuallyDrop<T> =
 pub enum PanicTrigManuallyDrop {}
 
 impl TrigManuallyDrop for PanicTrigManuallyDrop {
+	type Output = Infallible;
+
 	// Just a cold version of panic
 	#[inline(never)]
 	#[cold]
-	fn trig_next_invalid_beh(a: Arguments<'_>) -> trig_manuallydrop_returntype!() {
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> Infallible {
 		panic!("{}", a);
 	}
 }
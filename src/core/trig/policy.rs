@@ -0,0 +1,181 @@
+//! What happens to the value when the safe ManuallyDrop is dropped without releasing it
+//! (an implicit drop): the policy is chosen by the trigger (`TrigManuallyDrop::IMPLICIT_DROP_POLICY`)
+//! or for a type by wrapping its trigger in `WithImplicitDropPolicy`.
+//!
+//! ```rust
+//! use SafeManuallyDrop::core::trig::policy::{LeakAndTrigPolicy, WithImplicitDropPolicy};
+//! use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+//! use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+//!
+//! /// The destructor of the mapped buffer must never run implicitly,
+//! /// the buffer is leaked (as with std ManuallyDrop) and the violation is reported.
+//! type MappedBufferManuallyDrop<T> =
+//! 	AlwaysSafeManuallyDrop<T, WithImplicitDropPolicy<EmptyLoopTrigManuallyDrop, LeakAndTrigPolicy>>;
+//!
+//! let mut buffer = MappedBufferManuallyDrop::new(vec![0u8; 16]);
+//!
+//! #[allow(unused_unsafe)]
+//! unsafe {
+//! 	MappedBufferManuallyDrop::drop(&mut buffer);
+//! }
+//! ```
+
+use crate::core::report::ViolationReport;
+use crate::core::trig::TrigManuallyDrop;
+use core::fmt::Arguments;
+use core::fmt::Display;
+use core::marker::PhantomData;
+
+/// What happens to the value when the safe ManuallyDrop is dropped without releasing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImplicitDropPolicy {
+	/// The value is dropped, the trigger is not executed (like RAII).
+	DropSilently,
+	/// The value is dropped, then the trigger is executed (default).
+	DropAndTrig,
+	/// The value is leaked (exactly like std ManuallyDrop), the trigger is executed.
+	LeakAndTrig,
+	/// The value is leaked, the trigger is not executed.
+	LeakSilently,
+}
+
+impl ImplicitDropPolicy {
+	/// Whether the value is dropped.
+	#[inline]
+	pub const fn is_drop_value(self) -> bool {
+		matches!(self, Self::DropSilently | Self::DropAndTrig)
+	}
+
+	/// Whether the trigger is executed.
+	#[inline]
+	pub const fn is_trig(self) -> bool {
+		matches!(self, Self::DropAndTrig | Self::LeakAndTrig)
+	}
+}
+
+impl Default for ImplicitDropPolicy {
+	#[inline(always)]
+	fn default() -> Self {
+		Self::DropAndTrig
+	}
+}
+
+impl Display for ImplicitDropPolicy {
+	#[inline]
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		let str = match self {
+			Self::DropSilently => "drop silently",
+			Self::DropAndTrig => "drop and trig",
+			Self::LeakAndTrig => "leak and trig",
+			Self::LeakSilently => "leak silently",
+		};
+
+		Display::fmt(str, f)
+	}
+}
+
+/// A type that defines the implicit drop policy for `WithImplicitDropPolicy`.
+pub trait ImplicitDropPolicyType {
+	/// The implicit drop policy.
+	const POLICY: ImplicitDropPolicy;
+}
+
+/// The value is dropped, the trigger is not executed (like RAII).
+pub enum DropSilentlyPolicy {}
+
+impl ImplicitDropPolicyType for DropSilentlyPolicy {
+	const POLICY: ImplicitDropPolicy = ImplicitDropPolicy::DropSilently;
+}
+
+/// The value is dropped, then the trigger is executed.
+pub enum DropAndTrigPolicy {}
+
+impl ImplicitDropPolicyType for DropAndTrigPolicy {
+	const POLICY: ImplicitDropPolicy = ImplicitDropPolicy::DropAndTrig;
+}
+
+/// The value is leaked (exactly like std ManuallyDrop), the trigger is executed.
+pub enum LeakAndTrigPolicy {}
+
+impl ImplicitDropPolicyType for LeakAndTrigPolicy {
+	const POLICY: ImplicitDropPolicy = ImplicitDropPolicy::LeakAndTrig;
+}
+
+/// The value is leaked, the trigger is not executed.
+pub enum LeakSilentlyPolicy {}
+
+impl ImplicitDropPolicyType for LeakSilentlyPolicy {
+	const POLICY: ImplicitDropPolicy = ImplicitDropPolicy::LeakSilently;
+}
+
+/// The trigger `Trig` with the implicit drop policy `P`, all violations are passed to `Trig`.
+pub struct WithImplicitDropPolicy<Trig, P>(PhantomData<(Trig, P)>);
+
+impl<Trig, P> TrigManuallyDrop for WithImplicitDropPolicy<Trig, P>
+where
+	Trig: TrigManuallyDrop,
+	P: ImplicitDropPolicyType,
+{
+	type Output = Trig::Output;
+
+	const IMPLICIT_DROP_POLICY: ImplicitDropPolicy = P::POLICY;
+
+	const POISON_RELEASED: bool = Trig::POISON_RELEASED;
+
+	#[inline(always)]
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> Trig::Output {
+		Trig::trig_next_invalid_beh(a)
+	}
+
+	#[inline(always)]
+	fn trig_violation(report: &ViolationReport) -> Trig::Output {
+		Trig::trig_violation(report)
+	}
+}
+
+#[cfg(test)]
+#[test]
+fn test_implicit_drop_policy() {
+	use crate::beh::safe::SafeManuallyDrop;
+	use core::sync::atomic::AtomicUsize;
+	use core::sync::atomic::Ordering;
+
+	static TRIGS: AtomicUsize = AtomicUsize::new(0);
+	static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+	enum CountTrig {}
+	impl TrigManuallyDrop for CountTrig {
+		type Output = ();
+
+		fn trig_next_invalid_beh(_a: Arguments<'_>) {
+			TRIGS.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+
+	struct Value;
+	impl Drop for Value {
+		fn drop(&mut self) {
+			DROPS.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+
+	fn implicit_drop<P: ImplicitDropPolicyType>() -> (usize, usize) {
+		let (trigs, drops) = (TRIGS.load(Ordering::SeqCst), DROPS.load(Ordering::SeqCst));
+		drop(SafeManuallyDrop::<_, WithImplicitDropPolicy<CountTrig, P>>::new(Value));
+
+		(
+			TRIGS.load(Ordering::SeqCst) - trigs,
+			DROPS.load(Ordering::SeqCst) - drops,
+		)
+	}
+
+	assert_eq!(implicit_drop::<DropSilentlyPolicy>(), (0, 1));
+	assert_eq!(implicit_drop::<DropAndTrigPolicy>(), (1, 1));
+	assert_eq!(implicit_drop::<LeakAndTrigPolicy>(), (1, 0));
+	assert_eq!(implicit_drop::<LeakSilentlyPolicy>(), (0, 0));
+
+	assert_eq!(
+		<CountTrig as TrigManuallyDrop>::IMPLICIT_DROP_POLICY,
+		ImplicitDropPolicy::DropAndTrig
+	);
+}
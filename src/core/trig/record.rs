@@ -0,0 +1,600 @@
+//! Trigger that records the last violations in a fixed-size lock-free ring buffer.
+
+use crate::core::report::ManuallyDropOperation;
+use crate::core::report::ViolationKind;
+use crate::core::report::ViolationReport;
+use crate::core::state::StateManuallyDropData;
+use crate::core::trig::TrigManuallyDrop;
+use core::fmt::Arguments;
+use core::fmt::Display;
+use core::marker::PhantomData;
+use core::panic::Location;
+use core::ptr;
+use core::sync::atomic::fence;
+use core::sync::atomic::AtomicPtr;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::AtomicU8;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+
+/// A protected version of SafeManuallyDrop that records the undefined behavior
+/// of the ManuallyDrop logic (in the domain `D`) and continues.
+pub type AlwaysSafeRecordManuallyDrop<T, D = GlobalRecordDomain> =
+	crate::beh::safe::SafeManuallyDrop<T, RecordTrigManuallyDrop<D>>;
+
+/// A secure or non-secure version of SafeManuallyDrop that records the undefined
+/// behavior of the ManuallyDrop logic (in the domain `D`) and continues.
+pub type AutoSafeRecordManuallyDrop<T, D = GlobalRecordDomain> =
+	crate::beh::auto::AutoSafeManuallyDrop<T, RecordTrigManuallyDrop<D>>;
+
+/// A copy of the violation report without backtraces, stored in the ring buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ViolationRecord {
+	kind: ViolationKind,
+	state: Option<StateManuallyDropData>,
+	operation: Option<ManuallyDropOperation>,
+
+	type_name: &'static str,
+	address: usize,
+
+	release_location: Option<&'static Location<'static>>,
+	location: Option<&'static Location<'static>>,
+}
+
+impl ViolationRecord {
+	/// A record of a violation reported with only a message (`trig_next_invalid_beh`),
+	/// the kind is `ViolationKind::Unknown`, the other details are unknown
+	/// (as in `ViolationReport::unknown`).
+	#[inline]
+	pub const fn unknown() -> Self {
+		Self {
+			kind: ViolationKind::Unknown,
+			state: None,
+			operation: None,
+
+			type_name: "<unknown>",
+			address: 0,
+
+			release_location: None,
+			location: None,
+		}
+	}
+
+	/// Kind of violation.
+	#[inline]
+	pub const fn kind(&self) -> ViolationKind {
+		self.kind
+	}
+
+	/// The state of ManuallyDrop before the operation (None if it is unknown,
+	/// see `ViolationReport::state`).
+	#[inline]
+	pub const fn state(&self) -> Option<StateManuallyDropData> {
+		self.state
+	}
+
+	/// The attempted operation (None if it is unknown, see `ViolationReport::operation`).
+	#[inline]
+	pub const fn operation(&self) -> Option<ManuallyDropOperation> {
+		self.operation
+	}
+
+	/// Name of the type of the value in ManuallyDrop (`core::any::type_name`).
+	#[inline]
+	pub const fn type_name(&self) -> &'static str {
+		self.type_name
+	}
+
+	/// Address of the ManuallyDrop instance (0 if it is unknown, see `ViolationReport::address`).
+	#[inline]
+	pub const fn address(&self) -> usize {
+		self.address
+	}
+
+	/// The place in the code where the value was released for the first time.
+	#[inline]
+	pub const fn release_location(&self) -> Option<&'static Location<'static>> {
+		self.release_location
+	}
+
+	/// The place in the code where the violation was detected
+	/// (None for an implicit drop).
+	#[inline]
+	pub const fn location(&self) -> Option<&'static Location<'static>> {
+		self.location
+	}
+}
+
+impl From<&ViolationReport> for ViolationRecord {
+	#[inline]
+	fn from(report: &ViolationReport) -> Self {
+		Self {
+			kind: report.kind(),
+			state: report.state(),
+			operation: report.operation(),
+
+			type_name: report.type_name(),
+			address: report.address(),
+
+			release_location: report.release_location(),
+			location: report.location(),
+		}
+	}
+}
+
+impl Display for ViolationRecord {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+		let (state, operation) = match (self.state, self.operation) {
+			(Some(state), Some(operation)) => (state, operation),
+			_ => {
+				return write!(
+					f,
+					"{} of ManuallyDrop (reported with only a message)",
+					self.kind
+				);
+			}
+		};
+
+		write!(
+			f,
+			"{} ({}) of ManuallyDrop<{}>",
+			self.kind, operation, self.type_name
+		)?;
+		if let Some(location) = self.location {
+			write!(f, " at {}:{}", location.file(), location.line())?;
+		}
+		if let Some(location) = self.release_location {
+			write!(
+				f,
+				", first {} at {}:{}",
+				state.release_verb(),
+				location.file(),
+				location.line()
+			)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// One record of the ring buffer, protected by a sequence number.
+///
+/// All fields are atomic, so a reader racing with a writer never reads
+/// a torn value, and the sequence number tells it whether the record is consistent.
+/// A writer claims the slot with a CAS and never waits, if the slot is busy the record is lost.
+pub struct RecordSlot {
+	/// 0 - never written, odd - being written, `2 * (index + 1)` - written record `index`.
+	seq: AtomicU64,
+
+	kind: AtomicU8,
+	state: AtomicU8,
+	operation: AtomicU8,
+
+	type_name_ptr: AtomicPtr<u8>,
+	type_name_len: AtomicUsize,
+	address: AtomicUsize,
+
+	release_location: AtomicPtr<Location<'static>>,
+	location: AtomicPtr<Location<'static>>,
+}
+
+impl RecordSlot {
+	/// Create an empty slot.
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			seq: AtomicU64::new(0),
+
+			kind: AtomicU8::new(0),
+			state: AtomicU8::new(0),
+			operation: AtomicU8::new(0),
+
+			type_name_ptr: AtomicPtr::new(ptr::null_mut()),
+			type_name_len: AtomicUsize::new(0),
+			address: AtomicUsize::new(0),
+
+			release_location: AtomicPtr::new(ptr::null_mut()),
+			location: AtomicPtr::new(ptr::null_mut()),
+		}
+	}
+
+	#[inline(always)]
+	const fn seq_of(index: u64) -> u64 {
+		(index + 1) * 2
+	}
+
+	#[inline(always)]
+	fn location_to_ptr(location: Option<&'static Location<'static>>) -> *mut Location<'static> {
+		match location {
+			Some(a) => a as *const Location<'static> as *mut _,
+			None => ptr::null_mut(),
+		}
+	}
+
+	#[inline(always)]
+	fn location_from_ptr(location: *mut Location<'static>) -> Option<&'static Location<'static>> {
+		// only `&'static Location` is written to the pointer.
+		unsafe { location.as_ref() }
+	}
+
+	/// Write the record with the index, returns false if the record is lost: the slot
+	/// already contains a newer record or another writer is writing to it (the writer
+	/// never waits, it can be preempted or interrupted by a signal handler that also records).
+	fn write(&self, index: u64, record: &ViolationRecord) -> bool {
+		let new_seq = Self::seq_of(index);
+
+		// claim: even -> odd
+		let mut seq = self.seq.load(Ordering::Relaxed);
+		loop {
+			if seq >= new_seq || seq % 2 == 1 {
+				return false;
+			}
+
+			match self
+				.seq
+				.compare_exchange(seq, new_seq - 1, Ordering::Acquire, Ordering::Relaxed)
+			{
+				Ok(_) => break,
+				// the slot was claimed or written by another writer, it made progress
+				Err(a) => seq = a,
+			}
+		}
+		fence(Ordering::Release);
+
+		self.kind
+			.store(record.kind.index() as u8, Ordering::Relaxed);
+		let state = match (record.kind, record.state) {
+			// the corrupted byte is stored instead of the state
+			(ViolationKind::StateCorrupted { byte }, _) => byte,
+			(_, Some(state)) => state.into(),
+			(_, None) => u8::MAX,
+		};
+		self.state.store(state, Ordering::Relaxed);
+		let operation = match record.operation {
+			Some(operation) => operation.index() as u8,
+			None => u8::MAX,
+		};
+		self.operation.store(operation, Ordering::Relaxed);
+
+		self.type_name_ptr
+			.store(record.type_name.as_ptr() as *mut u8, Ordering::Relaxed);
+		self.type_name_len
+			.store(record.type_name.len(), Ordering::Relaxed);
+		self.address.store(record.address, Ordering::Relaxed);
+
+		self.release_location.store(
+			Self::location_to_ptr(record.release_location),
+			Ordering::Relaxed,
+		);
+		self.location
+			.store(Self::location_to_ptr(record.location), Ordering::Relaxed);
+
+		// release
+		self.seq.store(new_seq, Ordering::Release);
+
+		true
+	}
+
+	/// Read the record with the index, None if the slot does not contain it
+	/// (not yet written, being written or already overwritten).
+	fn read(&self, index: u64) -> Option<ViolationRecord> {
+		let seq = Self::seq_of(index);
+		if self.seq.load(Ordering::Acquire) != seq {
+			return None;
+		}
+
+		let kind = self.kind.load(Ordering::Relaxed);
+		let state = self.state.load(Ordering::Relaxed);
+		let operation = self.operation.load(Ordering::Relaxed);
+
+		let type_name_ptr = self.type_name_ptr.load(Ordering::Relaxed);
+		let type_name_len = self.type_name_len.load(Ordering::Relaxed);
+		let address = self.address.load(Ordering::Relaxed);
+
+		let release_location = self.release_location.load(Ordering::Relaxed);
+		let location = self.location.load(Ordering::Relaxed);
+
+		fence(Ordering::Acquire);
+		if self.seq.load(Ordering::Relaxed) != seq {
+			return None;
+		}
+
+		Some(ViolationRecord {
+			kind: match ViolationKind::ALL[kind as usize] {
+				ViolationKind::StateCorrupted { .. } => {
+					ViolationKind::StateCorrupted { byte: state }
+				}
+				kind => kind,
+			},
+			state: match kind as usize == ViolationKind::Unknown.index() {
+				true => None,
+				false => Some(
+					StateManuallyDropData::from_or_corrupted(state)
+						.unwrap_or(StateManuallyDropData::Poisoned),
+				),
+			},
+			operation: ManuallyDropOperation::ALL.get(operation as usize).copied(),
+
+			// the fields are consistent (the same seq), the pointer and the length
+			// are obtained from `&'static str`.
+			type_name: unsafe {
+				core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+					type_name_ptr,
+					type_name_len,
+				))
+			},
+			address,
+
+			release_location: Self::location_from_ptr(release_location),
+			location: Self::location_from_ptr(location),
+		})
+	}
+}
+
+impl Default for RecordSlot {
+	#[inline(always)]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Fixed-size lock-free ring buffer of the last violation records (works in no_std).
+///
+/// `RecordBuffer<[RecordSlot; N]>` is created in a static, and it is used
+/// as `RecordBuffer` (`RecordBuffer<[RecordSlot]>`).
+pub struct RecordBuffer<S: ?Sized = [RecordSlot]> {
+	/// The index of the next record.
+	head: AtomicU64,
+	/// The index of the first record that has not been drained.
+	tail: AtomicU64,
+	/// The number of records lost because their slot was busy.
+	lost: AtomicU64,
+
+	slots: S,
+}
+
+impl<const N: usize> RecordBuffer<[RecordSlot; N]> {
+	/// Create an empty buffer for the last `N` records.
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			head: AtomicU64::new(0),
+			tail: AtomicU64::new(0),
+			lost: AtomicU64::new(0),
+
+			slots: [const { RecordSlot::new() }; N],
+		}
+	}
+}
+
+impl<const N: usize> Default for RecordBuffer<[RecordSlot; N]> {
+	#[inline(always)]
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl RecordBuffer {
+	/// The maximum number of records kept in the buffer.
+	#[inline]
+	pub const fn capacity(&self) -> usize {
+		self.slots.len()
+	}
+
+	/// The total number of records written to the buffer (including overwritten and lost ones).
+	#[inline]
+	pub fn total(&self) -> u64 {
+		self.head.load(Ordering::Acquire)
+	}
+
+	/// The number of records lost because their slot was being written by another
+	/// writer at the same moment (the writers never wait for each other).
+	#[inline]
+	pub fn lost(&self) -> u64 {
+		self.lost.load(Ordering::Relaxed)
+	}
+
+	/// Write a record, the oldest record is overwritten if the buffer is full.
+	/// The record is lost (see `lost`) if its slot is being written by another writer.
+	pub fn push(&self, record: &ViolationRecord) {
+		let capacity = self.slots.len() as u64;
+		if capacity == 0 {
+			return;
+		}
+
+		let index = self.head.fetch_add(1, Ordering::AcqRel);
+		if !self.slots[(index % capacity) as usize].write(index, record) {
+			self.lost.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	/// The range of records that are not drained and not overwritten.
+	#[inline]
+	fn range(&self, tail: u64, head: u64) -> RecordIter<'_> {
+		let first = head.saturating_sub(self.slots.len() as u64);
+
+		RecordIter {
+			buffer: self,
+			next: tail.max(first),
+			end: head,
+		}
+	}
+
+	/// Iterate over the records that have not been drained, from the oldest
+	/// (records that are being overwritten at this moment are skipped).
+	#[inline]
+	pub fn iter(&self) -> RecordIter<'_> {
+		let tail = self.tail.load(Ordering::Acquire);
+		let head = self.head.load(Ordering::Acquire);
+
+		self.range(tail, head)
+	}
+
+	/// Take the records that have not been drained, from the oldest,
+	/// the next `iter`/`drain` will not return them.
+	#[inline]
+	pub fn drain(&self) -> RecordIter<'_> {
+		let head = self.head.load(Ordering::Acquire);
+		let tail = self.tail.fetch_max(head, Ordering::AcqRel);
+
+		self.range(tail, head)
+	}
+}
+
+/// Iterator over the records of `RecordBuffer`.
+pub struct RecordIter<'a> {
+	buffer: &'a RecordBuffer,
+	next: u64,
+	end: u64,
+}
+
+impl Iterator for RecordIter<'_> {
+	type Item = ViolationRecord;
+
+	fn next(&mut self) -> Option<ViolationRecord> {
+		let capacity = self.buffer.slots.len() as u64;
+
+		while self.next < self.end {
+			let index = self.next;
+			self.next += 1;
+
+			if let Some(a) = self.buffer.slots[(index % capacity) as usize].read(index) {
+				return Some(a);
+			}
+		}
+
+		None
+	}
+}
+
+/// A domain of the records, each domain has its own static ring buffer.
+///
+/// ```rust
+/// use SafeManuallyDrop::core::trig::record::{RecordBuffer, RecordDomain, RecordSlot};
+///
+/// enum MyPoolDomain {}
+///
+/// impl RecordDomain for MyPoolDomain {
+/// 	fn buffer() -> &'static RecordBuffer {
+/// 		static BUFFER: RecordBuffer<[RecordSlot; 16]> = RecordBuffer::new();
+///
+/// 		&BUFFER
+/// 	}
+/// }
+/// ```
+pub trait RecordDomain {
+	/// The ring buffer of the domain.
+	fn buffer() -> &'static RecordBuffer;
+}
+
+/// The global domain of the records (the last 32 records), used by default.
+pub enum GlobalRecordDomain {}
+
+impl RecordDomain for GlobalRecordDomain {
+	#[inline(always)]
+	fn buffer() -> &'static RecordBuffer {
+		static GLOBAL_RECORD_BUFFER: RecordBuffer<[RecordSlot; 32]> = RecordBuffer::new();
+
+		&GLOBAL_RECORD_BUFFER
+	}
+}
+
+/// On undefined behavior, ManuallyDrop records the violation in the ring buffer
+/// of the domain `D` and continues (the invalid operation is skipped).
+///
+/// (Violations without a report, `trig_next_invalid_beh`, are recorded
+/// as `ViolationRecord::unknown`.)
+pub struct RecordTrigManuallyDrop<D = GlobalRecordDomain>(PhantomData<D>);
+
+impl<D: RecordDomain> TrigManuallyDrop for RecordTrigManuallyDrop<D> {
+	type Output = ();
+
+	#[inline]
+	fn trig_next_invalid_beh(_a: Arguments<'_>) {
+		D::buffer().push(&ViolationRecord::unknown())
+	}
+
+	#[inline]
+	fn trig_violation(report: &ViolationReport) {
+		D::buffer().push(&ViolationRecord::from(report))
+	}
+}
+
+#[cfg(test)]
+#[test]
+fn test_record_trig_manuallydrop() {
+	enum TestDomain {}
+	impl RecordDomain for TestDomain {
+		fn buffer() -> &'static RecordBuffer {
+			static BUFFER: RecordBuffer<[RecordSlot; 2]> = RecordBuffer::new();
+
+			&BUFFER
+		}
+	}
+	type ManuallyDrop<T> = AlwaysSafeRecordManuallyDrop<T, TestDomain>;
+
+	let mut data = ManuallyDrop::new(1);
+	#[allow(unused_unsafe)]
+	unsafe {
+		ManuallyDrop::drop(&mut data);
+		ManuallyDrop::drop(&mut data);
+		ManuallyDrop::drop(&mut data);
+	}
+	assert!(ManuallyDrop::take_or_none(&mut data).is_none());
+
+	let buffer = TestDomain::buffer();
+	assert_eq!(buffer.capacity(), 2);
+	assert_eq!(buffer.total(), 3);
+
+	// the oldest record is overwritten
+	let mut iter = buffer.iter();
+	let record = iter.next().unwrap();
+	assert_eq!(record.kind(), ViolationKind::DoubleDrop);
+	assert_eq!(record.type_name(), core::any::type_name::<i32>());
+	assert!(record.location().is_some());
+	assert_eq!(iter.next().unwrap().kind(), ViolationKind::TakeAfterDrop);
+	assert!(iter.next().is_none());
+
+	assert_eq!(buffer.drain().count(), 2);
+	assert_eq!(buffer.iter().count(), 0);
+	assert_eq!(buffer.drain().count(), 0);
+
+	let mut data = ManuallyDrop::new(2);
+	ManuallyDrop::reinit(&mut data, 3);
+	let record = buffer.iter().next().unwrap();
+	assert_eq!(record.kind(), ViolationKind::ReinitWithoutRelease);
+	assert_eq!(record.operation(), Some(ManuallyDropOperation::Reinit));
+	#[allow(unused_unsafe)]
+	unsafe {
+		ManuallyDrop::drop(&mut data);
+	}
+
+	// a violation without a report is recorded as unknown
+	assert_eq!(buffer.drain().count(), 1);
+	RecordTrigManuallyDrop::<TestDomain>::trig_next_invalid_beh(format_args!("manual"));
+	let record = buffer.drain().next().unwrap();
+	assert_eq!(record, ViolationRecord::unknown());
+	assert_eq!(record.kind(), ViolationKind::Unknown);
+	assert_eq!(record.state(), None);
+	assert_eq!(record.operation(), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_record_busy_slot() {
+	let buffer = RecordBuffer::<[RecordSlot; 1]>::new();
+	let buffer: &RecordBuffer = &buffer;
+
+	// a writer was preempted in the middle of the slot
+	buffer.slots[0].seq.store(1, Ordering::Relaxed);
+	buffer.push(&ViolationRecord::unknown()); // <-- does not wait
+	assert_eq!(buffer.total(), 1);
+	assert_eq!(buffer.lost(), 1);
+	assert_eq!(buffer.iter().count(), 0);
+
+	// the writer finished
+	buffer.slots[0].seq.store(0, Ordering::Relaxed);
+	buffer.push(&ViolationRecord::unknown());
+	assert_eq!(buffer.lost(), 1);
+	assert_eq!(buffer.iter().count(), 1);
+}
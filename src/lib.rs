@@ -51,7 +51,8 @@ fn main() {
 			/*
 				thread 'main' panicked at 'Undefined behavior when using
 				ManuallyDrop(combo_replace_manudropstate), instead of the expected default
-				state, the current state: DropModeTrig.', src/core/trig/hook.rs:14:5
+				state, the current state: DropModeTrig, first dropped at src/main.rs:21 (track_location),
+				used again at src/main.rs:31.', src/core/trig/hook.rs:116:2
 			*/
 			ManuallyDrop::drop(&mut data); // INVALID, COMBO DROP
 		}
@@ -162,18 +163,16 @@ use std::ops::Deref;
 use SafeManuallyDrop::AlwaysSafeHookManuallyDrop as ManuallyDrop;
 
 fn main() {
-	unsafe {
-		ManuallyDrop::set_hook(|args| {
-			println!("!!!{:?}", args);
+	ManuallyDrop::set_hook(|args| {
+		println!("!!!{:?}", args);
 
-			for _ in 0..3 {
-				std::thread::sleep(std::time::Duration::from_millis(1000));
-			}
+		for _ in 0..3 {
+			std::thread::sleep(std::time::Duration::from_millis(1000));
+		}
 
-			println!("exit");
-			std::process::exit(0x0100);
-		});
-	}
+		println!("exit");
+		std::process::exit(0x0100);
+	});
 
 	let mut data = ManuallyDrop::new(vec![1, 2, 3, 4]);
 	println!("data: {:?}", data.deref());
@@ -194,13 +193,13 @@ fn main() {
 ### 4. counter
 
 ```rust
-// Let me remind you that CounterManuallyDrop by behavior allows undefined
-// behavior in the same way as ManuallyDrop, but, unlike ManuallyDrop,
-// Counter keeps a counter of the number of undefined behavior triggers.
+// Let me remind you that CounterManuallyDrop does not stop the program in case
+// of undefined behavior, unlike the other triggers, it keeps a counter of the number
+// of undefined behavior triggers, and the invalid operation is skipped (a repeated
+// drop does nothing, a repeated take returns None with take_or_none).
 
-// !!!!
-// CounterManuallyDrop is experimental and changes the behavior of
-// the trigger trait for all types.
+// CounterManuallyDrop is a non-fatal trigger (its output is `()`), other
+// triggers (panic, abort, loop, hook) remain fatal.
 
 #[cfg(feature = "support_count_trig")]
 use SafeManuallyDrop::AutoSafeCounterManuallyDrop as ManuallyDrop;
@@ -225,28 +224,29 @@ fn main() {
 
 	#[allow(unused_unsafe)] // feature !always_compatible_stdapi
 	unsafe {
-		assert_eq!(data.is_next_trig(), false); // VALID, triggers never fired
+		assert!(!data.is_next_trig()); // VALID, triggers never fired
 
 		// =================
 		// !!! ATTENTION !!!
 		// =================
 		// Procedure:
-		// 1. Free up memory and try to read it
-		// 2. Re-free memory
+		// 1. Free up memory
+		// 2. Re-free memory (the trigger returns, the drop is skipped)
+		// 3. Try to take the freed value (the trigger returns, None)
 		ManuallyDrop::drop(&mut data); // VALID
-		assert_eq!(data.is_next_trig(), true); // VALID, counter trigger worked.
+		assert!(data.is_next_trig()); // VALID, counter trigger worked.
 
-		ManuallyDrop::drop(&mut data); // <<-- INVALID BEH, COUNTER += 1 (=1), COMBO DROP
+		ManuallyDrop::drop(&mut data); // <<-- INVALID BEH, COUNTER += 1 (=1), COMBO DROP, SKIPPED
+		ManuallyDrop::drop(&mut data); // <<-- INVALID BEH, COUNTER += 1 (=2), COMBO DROP, SKIPPED
 	}
 
-	// !!! Reading an already freed value
-	println!("data: {:?}", &data); // <<-- INVALID BEH, COUNTER += 1 (=2)
-
 	// to avoid warning if the always_compatible_stdapi flag is not used (can be removed)
 	#[allow(unused_unsafe)]
-	let _data2 = unsafe { // <<-- INVALID BEH, COUNTER += 1 (=3)
-		// !!! Trying to get the freed value
-		ManuallyDrop::take(&mut data)
+	let _data2 = unsafe {
+		// <<-- INVALID BEH, COUNTER += 1 (=3)
+		// !!! Trying to get the freed value, None
+		// (reading or taking the freed value with `deref`/`take` is refused with a panic)
+		ManuallyDrop::take_or_none(&mut data)
 	};
 
 	#[cfg(feature = "support_count_trig")]
@@ -332,6 +332,44 @@ features = [
 // (crate::core::flags)
 "flags_table",
 
+// Support for the alloc library, closure hooks with captured state (set_hook_boxed).
+// (Automatically enabled by the features that require it, std includes alloc.)
+//"alloc",
+
+// Support for the standard library (automatically enabled by the features that require it).
+//"std",
+
+// Remember the place in the code (file:line) where a SafeManuallyDrop value was released for the first time,
+// the location is included in the report passed to the trigger and in the panic messages.
+// (Note that the state is a pointer larger, enabled by the backtrace feature.)
+//"track_location",
+
+// Capture a backtrace when a SafeManuallyDrop value is released and when undefined
+// behavior is detected, both backtraces are included in the report passed to the trigger.
+// (Note that this feature requires std.)
+//"backtrace",
+
+// The #[SafeManuallyDrop::test] attribute, the test fails on any violation of ManuallyDrop
+// (even with non-fatal triggers) or if a ManuallyDrop created in it was neither released
+// nor ignore_dropped. (Note that SafeManuallyDrop::new is no longer const, this feature requires std.)
+//"test_macro",
+
+// Registry of live SafeManuallyDrop (crate::core::registry): each ManuallyDrop is registered when
+// it is created and unregistered when it is released, live_instances(), report_outstanding() and
+// report_outstanding_at_exit() report the leaked values.
+// (Note that SafeManuallyDrop::new is no longer const, this feature requires std.)
+//"leak_registry",
+
+// TransparentSafeManuallyDrop (crate::beh::transparent): a safe version of ManuallyDrop with exactly
+// the layout of core::mem::ManuallyDrop<T> (repr(transparent)), the state is stored in a global
+// side table by the address of the value. (Note that this feature requires std.)
+//"side_table",
+
+// A magic canary next to the state byte of SafeManuallyDrop, a stray write over the state (buffer
+// overflow, ptr::write) almost certainly damages it and is reported as a corrupted state
+// (ViolationKind::StateCorrupted). (Note that the state is 4 bytes larger.)
+//"state_canary",
+
 // Trigs:
 //
 // Ability to determine if an empty loop trigger has been executed.
@@ -380,9 +418,12 @@ features = [
 #![allow(clippy::tabs_in_doc_comments)]
 #![allow(clippy::needless_doctest_main)]
 #![allow(clippy::let_and_return)]
-#![allow(clippy::needless_if)]
+#![allow(clippy::needless_ifs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
-#![cfg_attr(not(feature = "support_abort_trig"), no_std)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 use crate::beh::auto::cfg_if_safemode;
 
@@ -391,10 +432,34 @@ use crate::beh::auto::cfg_if_safemode;
 #[rustfmt::skip]
 pub use ::core::mem::ManuallyDrop as UnsafeStdManuallyDrop;
 
+/// The `#[SafeManuallyDrop::test]` attribute, the test fails on any violation
+/// of ManuallyDrop or on any ManuallyDrop that was neither released nor ignore_dropped
+/// (see `core::testing::run_in_test_scope`).
+#[cfg(feature = "test_macro")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test_macro")))]
+pub use SafeManuallyDrop_macro::test;
+
 /// The core of the library that defines the basic primitives.
 pub mod core {
 	pub mod state;
 
+	/// Structured description of the undefined behavior detected by ManuallyDrop.
+	pub mod report;
+
+	/// Helpers for testing code that uses ManuallyDrop.
+	#[cfg(feature = "std")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+	pub mod testing;
+
+	/// Registry of live ManuallyDrop, report of leaked values.
+	#[cfg(feature = "leak_registry")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "leak_registry")))]
+	pub mod registry;
+
+	/// Poisoning of the memory of the released values of the types that opt in
+	/// (`WithPoisonFill`, AddressSanitizer).
+	pub mod poison;
+
 	#[cfg_attr(docsrs, doc(cfg(feature = "flags_table")))]
 	#[cfg(any(test, feature = "flags_table"))]
 	pub mod flags;
@@ -422,6 +487,15 @@ pub mod beh {
 	pub mod auto;
 	pub mod safe;
 	pub mod r#unsafe;
+
+	/// Temporary lending of the value of ManuallyDrop (lend, with_taken).
+	pub mod lend;
+
+	/// A safe version of ManuallyDrop with the layout of `core::mem::ManuallyDrop<T>`,
+	/// the state is stored in a global side table.
+	#[cfg(feature = "side_table")]
+	#[cfg_attr(docsrs, doc(cfg(feature = "side_table")))]
+	pub mod transparent;
 }
 
 // PANIC
@@ -466,8 +540,8 @@ pub type AutoSafeHookManuallyDrop<T> = crate::core::trig::hook::AutoSafeHookManu
 // COUNTER
 /// A protected version of SafeManuallyDrop with a function to count
 /// the amount of undefined behavior of the ManuallyDrop logic.
-/// The undefined behavior of CounterManuallyDrop will be the same
-/// as when using the standard ManuallyDrop.
+/// After counting, the operation is skipped (drop) or refused with a panic (take, deref,
+/// replace, lend, into_inner), so the undefined behavior does not happen.
 #[cfg(feature = "support_count_trig")]
 #[cfg_attr(docsrs, doc(cfg(feature = "support_count_trig")))]
 pub type AlwaysSafeCounterManuallyDrop<T> =
@@ -475,8 +549,8 @@ pub type AlwaysSafeCounterManuallyDrop<T> =
 
 /// A secure or non-secure version of SafeManuallyDrop with a
 /// function to count the undefined behavior of the ManuallyDrop logic.
-/// The undefined behavior of CounterManuallyDrop will be the same as when
-/// using the standard ManuallyDrop.
+/// In the secure version, the operation is skipped or refused after counting
+/// (see `AlwaysSafeCounterManuallyDrop`).
 #[cfg(feature = "support_count_trig")]
 #[cfg_attr(docsrs, doc(cfg(feature = "support_count_trig")))]
 pub type AutoSafeCounterManuallyDrop<T> =
@@ -523,6 +597,40 @@ pub type AlwaysSafeManuallyDrop<T, Trig> = crate::beh::safe::SafeManuallyDrop<T,
 /// and compatibility in codes)
 pub type ManuallyDrop<T> = AutoSafeManuallyDrop<T>;
 
+// CELL
+/// A protected version of SafeManuallyDrop whose state is stored in `Cell<u8>`
+/// (without atomic operations, cheaper checks, the type is `!Sync`).
+pub type AlwaysSafeCellManuallyDrop<T, Trig> = crate::beh::safe::CellSafeManuallyDrop<T, Trig>;
+
+/// Depending on the build flag, a protected or an unprotected version of ManuallyDrop
+/// with a default trigger, the state is stored in `Cell<u8>` (without atomic operations,
+/// cheaper checks, the type is `!Sync`), for containers used by one thread.
+pub type AutoSafeCellManuallyDrop<T> =
+	crate::beh::auto::AutoSafeCellManuallyDrop<T, crate::core::trig::DefTrigManuallyDrop>;
+
+/// Depending on the build flag, a protected or an unprotected version of ManuallyDrop
+/// with a default trigger, the state is stored in `Cell<u8>` (alias to AutoSafeCellManuallyDrop).
+pub type CellManuallyDrop<T> = AutoSafeCellManuallyDrop<T>;
+
+// TRANSPARENT
+/// A protected version of ManuallyDrop with exactly the layout of `core::mem::ManuallyDrop<T>`,
+/// the state is stored in a global side table by the address of the value.
+#[cfg(feature = "side_table")]
+#[cfg_attr(docsrs, doc(cfg(feature = "side_table")))]
+pub type AlwaysSafeTransparentManuallyDrop<T, Trig> =
+	crate::beh::transparent::TransparentSafeManuallyDrop<T, Trig>;
+
+/// Depending on the build flag, a protected version of ManuallyDrop with the layout of
+/// `core::mem::ManuallyDrop<T>` (side table) or an unprotected version of ManuallyDrop
+/// with a default trigger, the layout is the same in both cases.
+#[cfg(feature = "side_table")]
+#[cfg_attr(docsrs, doc(cfg(feature = "side_table")))]
+pub type AutoSafeTransparentManuallyDrop<T> =
+	crate::beh::transparent::AutoSafeTransparentManuallyDrop<
+		T,
+		crate::core::trig::DefTrigManuallyDrop,
+	>;
+
 impl AutoSafeManuallyDrop<()> {
 	/// Depending on the build flag, a protected version of ManuallyDrop or
 	/// an unprotected version of ManuallyDrop with a default trigger.
@@ -116,8 +116,16 @@ macro_rules! __codegen {
 		)?
 	] => {
 		use crate::core::state::StateManuallyDropData;
+		use crate::core::state::ManuallyDropError;
+		#[allow(unused_imports)]
+		use crate::core::report::ManuallyDropOperation;
 		#[allow(unused_imports)]
 		use crate::core::state::StateManuallyDrop;
+		use crate::core::state::StateStorage;
+		#[allow(unused_imports)]
+		use crate::beh::lend::LendGuard;
+		#[allow(unused_imports)]
+		use crate::beh::lend::LentPanicGuard;
 		use ::core::ops::DerefMut;
 		use ::core::ops::Deref;
 		use ::core::fmt::Debug;
@@ -156,19 +164,57 @@ macro_rules! __codegen {
 			$($all:tt)+
 		)?
 	] => {
-		impl<T, Trig> $current_type<T, Trig> where Trig: TrigManuallyDrop {
-			/// Wrap a value to be manually dropped.
-			#[inline]
-			pub const fn new(value: T) -> Self {
-				let value = UnsafeStdManuallyDrop::new(value);
+		impl<T, Trig, S> $current_type<T, Trig, S> where Trig: TrigManuallyDrop, S: StateStorage {
+			$crate::macro_codegen::__if_codegen! {
+				if (#$is_safe) {
+					/// Wrap a value to be manually dropped.
+					///
+					/// (With the `test_macro` feature, the value is tracked by the test scope
+					/// of `#[SafeManuallyDrop::test]`, with the `leak_registry` feature, the value
+					/// is registered in the registry of live ManuallyDrop, so this function is not const.)
+					#[cfg(any(feature = "test_macro", feature = "leak_registry"))]
+					#[track_caller]
+					#[inline]
+					pub fn new(value: T) -> Self {
+						let value = UnsafeStdManuallyDrop::new(value);
 
-				unsafe {
-					Self::from_std(value)
+						let sself = unsafe {
+							Self::from_std(value)
+						};
+						sself.state.__created::<T>();
+
+						sself
+					}
+
+					/// Wrap a value to be manually dropped.
+					#[cfg(not(any(feature = "test_macro", feature = "leak_registry")))]
+					#[inline]
+					pub const fn new(value: T) -> Self {
+						let value = UnsafeStdManuallyDrop::new(value);
+
+						unsafe {
+							Self::from_std(value)
+						}
+					}
+				} else {
+					/// Wrap a value to be manually dropped.
+					#[inline]
+					pub const fn new(value: T) -> Self {
+						let value = UnsafeStdManuallyDrop::new(value);
+
+						unsafe {
+							Self::from_std(value)
+						}
+					}
 				}
 			}
 
 			/// Wrap a value to be manually dropped.
 			/// Unsafe because the UnsafeStdManuallyDrop input argument is in an undefined state.
+			///
+			/// # Safety
+			///
+			/// The value in `UnsafeStdManuallyDrop` must not have been released (dropped or taken).
 			#[inline]
 			pub const unsafe fn from_std($new_value_fn: UnsafeStdManuallyDrop<T>) -> Self {
 				$new_fn
@@ -176,6 +222,7 @@ macro_rules! __codegen {
 
 			/// Forgets value (similar to core::mem::forget), if you need to forget a
 			/// value in ManuallyData use ignore_drop() instead of this function.
+			#[track_caller]
 			#[inline]
 			pub fn forget(value: T) {
 				let sself = Self::new(value);
@@ -189,9 +236,13 @@ macro_rules! __codegen {
 			$crate::macro_codegen::__if_codegen! {
 				if (#$is_safe) {
 					/// Extracts the value from the ManuallyDrop container.
+					#[track_caller]
 					#[inline]
-					pub /*const*/ fn into_inner(slot: $current_type<T, Trig>) -> T {
-						slot.state.to_intoinnermode_or_trig::<Trig>();
+					pub /*const*/ fn into_inner(slot: $current_type<T, Trig, S>) -> T {
+						// the container is moved into the function, the address of the instance is unknown.
+						if !slot.state.to_intoinnermode_or_trig::<Trig, T>(::core::ptr::null()) {
+							slot.state.__refuse_operation(ManuallyDropOperation::IntoInner);
+						}
 
 						// into_inner
 						let mut slot = slot;
@@ -202,37 +253,313 @@ macro_rules! __codegen {
 							)
 						};
 
-						let _ignore_drop = UnsafeStdManuallyDrop::new(slot);
+						// the container is forgotten, only its state (with side allocations) is released.
+						let mut ignore_drop = UnsafeStdManuallyDrop::new(slot);
+						unsafe {
+							::core::ptr::drop_in_place(&mut ignore_drop.state);
+						}
 						value
 					}
 
 					/// Extracts the value from the ManuallyDrop container.
+					#[track_caller]
 					#[inline]
-					pub /*const*/ fn into_core_inner(slot: $current_type<T, Trig>) -> UnsafeStdManuallyDrop<T> {
-						slot.state.to_intoinnermode_or_trig::<Trig>();
+					pub /*const*/ fn into_core_inner(slot: $current_type<T, Trig, S>) -> UnsafeStdManuallyDrop<T> {
+						// the container is moved into the function, the address of the instance is unknown.
+						if !slot.state.to_intoinnermode_or_trig::<Trig, T>(::core::ptr::null()) {
+							slot.state.__refuse_operation(ManuallyDropOperation::IntoInner);
+						}
 
 						// analog UnsafeManuallyDrop::take
 						let mandrop: UnsafeStdManuallyDrop<T> = unsafe {
 							::core::ptr::read(slot.as_unsafestd_manuallydrop())
 						};
 
-						let _ignore_drop = UnsafeStdManuallyDrop::new(slot);
+						// the container is forgotten, only its state (with side allocations) is released.
+						let mut ignore_drop = UnsafeStdManuallyDrop::new(slot);
+						unsafe {
+							::core::ptr::drop_in_place(&mut ignore_drop.state);
+						}
 						mandrop
 					}
+
+					/// Extracts the value from the ManuallyDrop container, or returns the container
+					/// with the current state if the value has already been released
+					/// (the trigger is not executed).
+					#[track_caller]
+					#[inline]
+					pub fn try_into_inner(slot: $current_type<T, Trig, S>) -> Result<T, ($current_type<T, Trig, S>, ManuallyDropError)> {
+						if let Err(e) = slot.state.try_to_intoinnermode() {
+							return Err((slot, e));
+						}
+
+						let mut slot = slot;
+						let value: T = unsafe {
+							UnsafeStdManuallyDrop::take(
+								slot.as_mut_unsafestd_manuallydrop()
+							)
+						};
+
+						// the container is forgotten, only its state (with side allocations) is released.
+						let mut ignore_drop = UnsafeStdManuallyDrop::new(slot);
+						unsafe {
+							::core::ptr::drop_in_place(&mut ignore_drop.state);
+						}
+						Ok(value)
+					}
+
+					/// Takes the value from the ManuallyDrop<T> container out, or returns the current
+					/// state if the value has already been released (the trigger is not executed).
+					#[track_caller]
+					#[inline]
+					pub fn try_take(slot: &mut $current_type<T, Trig, S>) -> Result<T, ManuallyDropError> {
+						slot.state.try_to_takemode()?;
+
+						let value = unsafe { // the state is checked, the value has not been released
+							UnsafeStdManuallyDrop::take(&mut slot.value)
+						};
+						Self::__poison_released(slot);
+
+						Ok(value)
+					}
+
+					/// Takes the value from the ManuallyDrop<T> container out, or executes the trigger
+					/// and returns None if the value has already been released
+					/// (with a non-fatal trigger, for example, the counter).
+					#[track_caller]
+					#[inline]
+					pub fn take_or_none(slot: &mut $current_type<T, Trig, S>) -> Option<T> {
+						if !slot.state.to_takemode_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+							return None;
+						}
+
+						let value = unsafe { // the state is checked, the value has not been released
+							UnsafeStdManuallyDrop::take(&mut slot.value)
+						};
+						Self::__poison_released(slot);
+
+						Some(value)
+					}
+
+					/// Writes a new value to the released ManuallyDrop (after drop, take or ignore_drop)
+					/// and resets it to the initial state, so that the slot can be used again.
+					///
+					/// The trigger is executed if the value has not been released (the old value would
+					/// be leaked), if the trigger returns, the new value is dropped and the old value is kept.
+					#[track_caller]
+					#[inline]
+					pub fn reinit(slot: &mut $current_type<T, Trig, S>, value: T) {
+						if !slot.state.reinit_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+							return;
+						}
+
+						slot.__unpoison_released();
+						slot.value = UnsafeStdManuallyDrop::new(value);
+						unsafe { // the value is written, the state is empty again
+							slot.state.get_and_reset();
+						}
+					}
+
+					/// Replaces the value of ManuallyDrop with a new one, returns the old value
+					/// (the state does not change).
+					///
+					/// The trigger is executed if the value has already been released.
+					#[track_caller]
+					#[inline]
+					pub fn replace(slot: &mut $current_type<T, Trig, S>, value: T) -> T {
+						if !slot.state.check_or_trig::<Trig, T>(slot as *const Self as *const (), ManuallyDropOperation::Replace) {
+							slot.state.__refuse_operation(ManuallyDropOperation::Replace);
+						}
+
+						::core::mem::replace(&mut *slot.value, value)
+					}
+
+					/// Returns the value of ManuallyDrop, if the value has been released (after drop,
+					/// take or ignore_drop), a new value is created by `f` and written (see `reinit`).
+					///
+					/// The trigger is executed if the value is lent (see `lend`), is being dropped
+					/// or if the state is corrupted (it is unknown whether the value was released).
+					#[track_caller]
+					#[inline]
+					pub fn get_or_reinit(slot: &mut $current_type<T, Trig, S>, f: impl FnOnce() -> T) -> &mut T {
+						let state = slot.state.read();
+						if state.is_lent() || state.is_in_drop() || slot.state.read_corrupted_byte().is_some() {
+							// the value is not in the slot, is being dropped or may not have been released,
+							// it cannot be reinitialized
+							slot.state.check_or_trig::<Trig, T>(slot as *const Self as *const (), ManuallyDropOperation::Deref);
+							slot.state.__refuse_operation(ManuallyDropOperation::Deref);
+						}
+						if slot.state.is_next_trig() {
+							let value = f();
+							slot.__unpoison_released();
+							slot.value = UnsafeStdManuallyDrop::new(value);
+							unsafe { // the value is written, the state is empty again
+								slot.state.get_and_reset();
+							}
+						}
+
+						&mut slot.value
+					}
+
+					/// Temporarily moves the value out of ManuallyDrop, the value is put back when
+					/// the returned guard is dropped (including during unwinding). Until then the
+					/// ManuallyDrop is lent, any access to it executes the trigger.
+					///
+					/// The trigger is executed if the value has already been released.
+					#[track_caller]
+					#[inline]
+					pub fn lend(slot: &mut $current_type<T, Trig, S>) -> LendGuard<'_, T, S> {
+						if !slot.state.to_lentmode_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+							slot.state.__refuse_operation(ManuallyDropOperation::Lend);
+						}
+
+						let value = unsafe { // the state is lent, the value is moved out once
+							UnsafeStdManuallyDrop::take(&mut slot.value)
+						};
+						LendGuard::__new(&mut slot.value, Some(&slot.state), value)
+					}
+
+					/// Moves the value out of ManuallyDrop into `f`, `f` returns the value back
+					/// with the result. Until then the ManuallyDrop is lent, any access to it
+					/// executes the trigger. If `f` panics, the value is lost and ManuallyDrop
+					/// is considered taken.
+					///
+					/// The trigger is executed if the value has already been released.
+					#[track_caller]
+					#[inline]
+					pub fn with_taken<R>(slot: &mut $current_type<T, Trig, S>, f: impl FnOnce(T) -> (T, R)) -> R {
+						if !slot.state.to_lentmode_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+							slot.state.__refuse_operation(ManuallyDropOperation::Lend);
+						}
+
+						let value = unsafe { // the state is lent, the value is moved out once
+							UnsafeStdManuallyDrop::take(&mut slot.value)
+						};
+						let on_panic = LentPanicGuard::__new(&slot.state);
+						let (value, result) = f(value);
+						on_panic.disarm();
+
+						slot.value = UnsafeStdManuallyDrop::new(value);
+						slot.state.__return_lent();
+
+						result
+					}
 				}else {
 					/// Extracts the value from the ManuallyDrop container.
 					/// !!!(The unsafe version of the function is identical to the UnsafeStdManuallyDrop::into_inner core.)
+					#[track_caller]
 					#[inline]
-					pub const fn into_inner(slot: $current_type<T, Trig>) -> T {
+					pub const fn into_inner(slot: $current_type<T, Trig, S>) -> T {
 						UnsafeStdManuallyDrop::into_inner($current_type::into_core_inner(slot))
 					}
 
 					/// Extracts the value from the ManuallyDrop container.
 					/// !!!(The unsafe version of the function is identical to the UnsafeStdManuallyDrop::into_inner core.)
+					#[track_caller]
 					#[inline]
-					pub const fn into_core_inner(slot: $current_type<T, Trig>) -> UnsafeStdManuallyDrop<T> {
+					pub const fn into_core_inner(slot: $current_type<T, Trig, S>) -> UnsafeStdManuallyDrop<T> {
 						slot.value
 					}
+
+					/// Extracts the value from the ManuallyDrop container, or returns the container
+					/// with the current state if the value has already been released
+					/// (the trigger is not executed).
+					/// !!!(The unsafe version is stateless and always returns the value.)
+					#[inline]
+					pub const fn try_into_inner(slot: $current_type<T, Trig, S>) -> Result<T, ($current_type<T, Trig, S>, ManuallyDropError)> {
+						Ok($current_type::into_inner(slot))
+					}
+
+					/// Takes the value from the ManuallyDrop<T> container out, or returns the current
+					/// state if the value has already been released (the trigger is not executed).
+					/// !!!(The unsafe version is stateless and always takes the value, as UnsafeStdManuallyDrop::take.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn try_take(slot: &mut $current_type<T, Trig, S>) -> Result<T, ManuallyDropError> {
+						Ok(UnsafeStdManuallyDrop::take(&mut slot.value))
+					}
+
+					/// Takes the value from the ManuallyDrop<T> container out, or executes the trigger
+					/// and returns None if the value has already been released
+					/// (with a non-fatal trigger, for example, the counter).
+					/// !!!(The unsafe version is stateless and always takes the value, as UnsafeStdManuallyDrop::take.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn take_or_none(slot: &mut $current_type<T, Trig, S>) -> Option<T> {
+						Some(UnsafeStdManuallyDrop::take(&mut slot.value))
+					}
+
+					/// Writes a new value to the released ManuallyDrop (after drop, take or ignore_drop)
+					/// and resets it to the initial state, so that the slot can be used again.
+					/// !!!(The unsafe version is stateless and always writes the value, the value must have been released.)
+					///
+					/// # Safety
+					///
+					/// The value must have been released, the unsafe version does not check it (the old value would be leaked).
+					#[inline]
+					pub unsafe fn reinit(slot: &mut $current_type<T, Trig, S>, value: T) {
+						slot.value = UnsafeStdManuallyDrop::new(value);
+					}
+
+					/// Replaces the value of ManuallyDrop with a new one, returns the old value.
+					/// !!!(The unsafe version is stateless, the value must not have been released.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn replace(slot: &mut $current_type<T, Trig, S>, value: T) -> T {
+						::core::mem::replace(&mut *slot.value, value)
+					}
+
+					/// Returns the value of ManuallyDrop, if the value has been released (after drop,
+					/// take or ignore_drop), a new value is created by `f` and written (see `reinit`).
+					/// !!!(Not supported in the unsafe version, the state is unknown, the current value is always returned.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn get_or_reinit(slot: &mut $current_type<T, Trig, S>, f: impl FnOnce() -> T) -> &mut T {
+						let _f = f;
+
+						&mut slot.value
+					}
+
+					/// Temporarily moves the value out of ManuallyDrop, the value is put back when
+					/// the returned guard is dropped (including during unwinding).
+					/// !!!(The unsafe version is stateless, the value must not have been released.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn lend(slot: &mut $current_type<T, Trig, S>) -> LendGuard<'_, T, S> {
+						let value = UnsafeStdManuallyDrop::take(&mut slot.value);
+
+						LendGuard::__new(&mut slot.value, None, value)
+					}
+
+					/// Moves the value out of ManuallyDrop into `f`, `f` returns the value back
+					/// with the result.
+					/// !!!(The unsafe version is stateless, the value must not have been released, if `f` panics, the value is lost.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn with_taken<R>(slot: &mut $current_type<T, Trig, S>, f: impl FnOnce(T) -> (T, R)) -> R {
+						let (value, result) = f(UnsafeStdManuallyDrop::take(&mut slot.value));
+						slot.value = UnsafeStdManuallyDrop::new(value);
+
+						result
+					}
 				}
 			}
 
@@ -241,32 +568,48 @@ macro_rules! __codegen {
 			$crate::macro_codegen::__codegen_compatible_stdapi_ornot! {
 				#if_compatible_stdapi_and_safeapi (#is_always_compatible: $is_always_compatible || ( #is_feature && #is_maybe_compatible: $is_maybe_compatible)) {
 					/// Takes the value from the ManuallyDrop<T> container out.
+					///
+					/// # Safety
+					///
+					/// The value must not be used after it was taken (as `core::mem::ManuallyDrop::take`), the safe version checks the state and executes the trigger.
+					#[track_caller]
 					#[inline]
-					pub unsafe fn take(slot: &mut $current_type<T, Trig>) -> T {
+					pub unsafe fn take(slot: &mut $current_type<T, Trig, S>) -> T {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								slot.state.to_takemode_or_trig::<Trig>();
+								if !slot.state.to_takemode_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+									slot.state.__refuse_operation(ManuallyDropOperation::Take);
+								}
 							}
 						}
 
 						#[allow(unused_unsafe)]
-						unsafe { // library provides security guarantees
+						let value = unsafe { // library provides security guarantees
 							UnsafeStdManuallyDrop::take(&mut slot.value)
-						}
+						};
+						Self::__poison_released(slot);
+
+						value
 					}
 				} else {
 					/// Takes the value from the ManuallyDrop<T> container out.
+					#[track_caller]
 					#[inline]
-					pub fn take(slot: &mut $current_type<T, Trig>) -> T {
+					pub fn take(slot: &mut $current_type<T, Trig, S>) -> T {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								slot.state.to_takemode_or_trig::<Trig>();
+								if !slot.state.to_takemode_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+									slot.state.__refuse_operation(ManuallyDropOperation::Take);
+								}
 							}
 						}
 
-						unsafe { // library provides security guarantees
+						let value = unsafe { // library provides security guarantees
 							UnsafeStdManuallyDrop::take(&mut slot.value)
-						}
+						};
+						Self::__poison_released(slot);
+
+						value
 					}
 				}
 			}
@@ -274,17 +617,53 @@ macro_rules! __codegen {
 
 		}
 
-		impl<T, Trig> $current_type<T, Trig> where T: ?Sized, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> $current_type<T, Trig, S> where T: ?Sized, Trig: TrigManuallyDrop, S: StateStorage {
+			/// The value was released (drop, take), if the trigger opted in (`Trig::POISON_RELEASED`),
+			/// its bytes are filled with `POISON_FILL_BYTE` and under AddressSanitizer they are poisoned
+			/// (only in the safe version, the unsafe version is stateless). The released ManuallyDrop
+			/// of such a type must not be moved until reinit (see `core::poison`).
+			#[inline(always)]
+			fn __poison_released(slot: &mut $current_type<T, Trig, S>) {
+				if $is_safe && Trig::POISON_RELEASED {
+					unsafe { // the value was released, the type opted in
+						$crate::core::poison::poison_released(&mut slot.value);
+					}
+				}
+			}
+
+			/// A new value is written to the released ManuallyDrop or it is destroyed,
+			/// under AddressSanitizer its bytes are unpoisoned.
+			#[cfg(safemanuallydrop_asan)]
+			#[inline(always)]
+			fn __unpoison_released(&self) {
+				if $is_safe && Trig::POISON_RELEASED {
+					$crate::core::poison::unpoison_released(&self.value);
+				}
+			}
+
+			/// A new value is written to the released ManuallyDrop or it is destroyed,
+			/// under AddressSanitizer its bytes are unpoisoned.
+			#[cfg(not(safemanuallydrop_asan))]
+			#[inline(always)]
+			fn __unpoison_released(&self) {}
+
 			// TODO! duplication of code, it could have been solved if the rust would allow
 			// it to be done somehow differently, but at this stage it’s the only way.
 			$crate::macro_codegen::__codegen_compatible_stdapi_ornot! {
 				#if_compatible_stdapi_and_safeapi (#is_always_compatible: $is_always_compatible || ( #is_feature && #is_maybe_compatible: $is_maybe_compatible)) {
 					/// Get reference to value.
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[track_caller]
 					#[inline]
 					pub unsafe fn as_value(&self) -> &T {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								self.state.deref_or_trig::<Trig>();
+								if !self.state.deref_or_trig::<Trig, T>(self as *const Self as *const ()) {
+									self.state.__refuse_operation(ManuallyDropOperation::Deref);
+								}
 							}
 						}
 
@@ -292,11 +671,18 @@ macro_rules! __codegen {
 					}
 
 					/// Get a mutable reference to a value.
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[track_caller]
 					#[inline]
 					pub unsafe fn as_mut_value(&mut self) -> &mut T {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								self.state.deref_or_trig::<Trig>();
+								if !self.state.deref_or_trig::<Trig, T>(self as *const Self as *const ()) {
+									self.state.__refuse_operation(ManuallyDropOperation::Deref);
+								}
 							}
 						}
 
@@ -306,6 +692,11 @@ macro_rules! __codegen {
 					/// PSEUDO SAFE! In safe ManuallyDrop, this function prevents undefined behavior at run time,
 					/// but the resulting raw pointer is not protected and does not depend on lifetime
 					/// and may be dangling.
+					///
+					/// # Safety
+					///
+					/// The pointer is not checked and may be dangling, the value must not have been released while it is used.
+					#[track_caller]
 					#[inline(always)]
 					pub unsafe fn as_ptr(&self) -> *const T {
 						// TODO, VALID?, Exp: ManuallyDrop::as_ptr
@@ -315,6 +706,11 @@ macro_rules! __codegen {
 					/// PSEUDO SAFE! In safe ManuallyDrop, this function prevents undefined behavior at run time,
 					/// but the resulting raw pointer is not protected and does not depend on lifetime
 					/// and may be dangling.
+					///
+					/// # Safety
+					///
+					/// The pointer is not checked and may be dangling, the value must not have been released while it is used.
+					#[track_caller]
 					#[inline(always)]
 					pub unsafe fn as_mut_ptr(&mut self) -> *mut T {
 						// TODO, VALID?, Exp: ManuallyDrop::as_mut_ptr
@@ -322,40 +718,65 @@ macro_rules! __codegen {
 					}
 
 					/// Manually drops the contained value.
+					///
+					/// # Safety
+					///
+					/// The value must not be used after it was dropped (as `core::mem::ManuallyDrop::drop`), the safe version checks the state and executes the trigger.
+					#[track_caller]
 					#[inline]
-					pub unsafe fn drop(slot: &mut $current_type<T, Trig>) {
+					pub unsafe fn drop(slot: &mut $current_type<T, Trig, S>) {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								slot.state.to_dropmode_or_trig::<Trig>();
+								if !slot.state.to_indropmode_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+									// the trigger returned, the value has already been released (recovery mode)
+									return;
+								}
+
+								// re-entrancy and a panic of the destructor are tracked by the state
+								slot.state.__in_drop(|| UnsafeStdManuallyDrop::drop(&mut slot.value));
+							} else {
+								UnsafeStdManuallyDrop::drop(&mut slot.value);
 							}
 						}
-
-						UnsafeStdManuallyDrop::drop(&mut slot.value)
+						Self::__poison_released(slot);
 					}
 
 					$crate::macro_codegen::__if_codegen! {
 						if (#$is_safe) {
 							/// Note that the safe ManuallyDrop checks to see if the value is freed when the safe ManuallyDrop struct dies.
 							/// The version of mem::forget is adapted for safe and insecure ManuallyDrop.
+							///
+							/// # Safety
+							///
+							/// The value must not be used after ignore_drop.
+							#[track_caller]
 							#[inline(always)]
 							pub unsafe fn ignore_drop(&self) {
-								self.state.to_ignore_trig_when_drop::<Trig>();
+								self.state.to_ignore_trig_when_drop::<Trig, T>(self as *const Self as *const ());
 							}
 						} else {
 							/// Note that the safe ManuallyDrop checks to see if the value is freed when the safe ManuallyDrop struct dies.
 							/// The version of mem::forget is adapted for safe and insecure ManuallyDrop.
 							/// !!!(Not supported in insecure version, does nothing).
+							///
+							/// # Safety
+							///
+							/// The value must not be used after ignore_drop.
+							#[track_caller]
 							#[inline(always)]
 							pub const unsafe fn ignore_drop(&self) {}
 						}
 					}
 				} else {
 					/// Get reference to value.
+					#[track_caller]
 					#[inline]
 					pub fn as_value(&self) -> &T {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								self.state.deref_or_trig::<Trig>();
+								if !self.state.deref_or_trig::<Trig, T>(self as *const Self as *const ()) {
+									self.state.__refuse_operation(ManuallyDropOperation::Deref);
+								}
 							}
 						}
 
@@ -365,11 +786,14 @@ macro_rules! __codegen {
 					}
 
 					/// Get a mutable reference to a value.
+					#[track_caller]
 					#[inline]
 					pub fn as_mut_value(&mut self) -> &mut T {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								self.state.deref_or_trig::<Trig>();
+								if !self.state.deref_or_trig::<Trig, T>(self as *const Self as *const ()) {
+									self.state.__refuse_operation(ManuallyDropOperation::Deref);
+								}
 							}
 						}
 
@@ -381,6 +805,7 @@ macro_rules! __codegen {
 					/// PSEUDO SAFE! In safe ManuallyDrop, this function prevents undefined behavior at run time,
 					/// but the resulting raw pointer is not protected and does not depend on lifetime
 					/// and may be dangling.
+					#[track_caller]
 					#[inline(always)]
 					pub fn as_ptr(&self) -> *const T {
 						// TODO, VALID?, Exp: ManuallyDrop::as_ptr
@@ -390,6 +815,7 @@ macro_rules! __codegen {
 					/// PSEUDO SAFE! In safe ManuallyDrop, this function prevents undefined behavior at run time,
 					/// but the resulting raw pointer is not protected and does not depend on lifetime
 					/// and may be dangling.
+					#[track_caller]
 					#[inline(always)]
 					pub fn as_mut_ptr(&mut self) -> *mut T {
 						// TODO, VALID?, Exp: ManuallyDrop::as_mut_ptr
@@ -397,31 +823,45 @@ macro_rules! __codegen {
 					}
 
 					/// Manually drops the contained value.
+					// clippy::should_implement_trait why?: the same name as in std ManuallyDrop::drop, it takes the slot and not self.
+					#[allow(clippy::should_implement_trait)]
+					#[track_caller]
 					#[inline]
-					pub fn drop(slot: &mut $current_type<T, Trig>) {
+					pub fn drop(slot: &mut $current_type<T, Trig, S>) {
 						$crate::macro_codegen::__if_codegen! {
 							if (#$is_safe) {
-								slot.state.to_dropmode_or_trig::<Trig>();
+								if !slot.state.to_indropmode_or_trig::<Trig, T>(slot as *const Self as *const ()) {
+									// the trigger returned, the value has already been released (recovery mode)
+									return;
+								}
+
+								// re-entrancy and a panic of the destructor are tracked by the state
+								slot.state.__in_drop(|| unsafe { // library provides security guarantees
+									UnsafeStdManuallyDrop::drop(&mut slot.value)
+								});
+							} else {
+								unsafe { // library provides security guarantees
+									UnsafeStdManuallyDrop::drop(&mut slot.value)
+								}
 							}
 						}
-
-						unsafe { // library provides security guarantees
-							UnsafeStdManuallyDrop::drop(&mut slot.value)
-						}
+						Self::__poison_released(slot);
 					}
 
 					$crate::macro_codegen::__if_codegen! {
 						if (#$is_safe) {
 							/// Note that the safe ManuallyDrop checks to see if the value is freed when the safe ManuallyDrop struct dies.
 							/// The version of mem::forget is adapted for safe and insecure ManuallyDrop.
+							#[track_caller]
 							#[inline(always)]
 							pub fn ignore_drop(&self) {
-								self.state.to_ignore_trig_when_drop::<Trig>();
+								self.state.to_ignore_trig_when_drop::<Trig, T>(self as *const Self as *const ());
 							}
 						} else {
 							/// Note that the safe ManuallyDrop checks to see if the value is freed when the safe ManuallyDrop struct dies.
 							/// The version of mem::forget is adapted for safe and insecure ManuallyDrop.
 							/// !!!(Not supported in insecure version, does nothing).
+							#[track_caller]
 							#[inline(always)]
 							pub const fn ignore_drop(&self) {}
 						}
@@ -431,6 +871,11 @@ macro_rules! __codegen {
 			}
 
 
+			/// Get a reference to the std ManuallyDrop.
+			///
+			/// # Safety
+			///
+			/// The state of ManuallyDrop is bypassed, the value must not have been released.
 			#[inline(always)]
 			pub const unsafe fn as_unsafestd_manuallydrop(&self) -> &UnsafeStdManuallyDrop<T> {
 				let $sself_as_unsafestd_manuallydrop = self;
@@ -438,6 +883,11 @@ macro_rules! __codegen {
 				$as_unsafestd_manuallydrop
 			}
 
+			/// Get a mutable reference to the std ManuallyDrop.
+			///
+			/// # Safety
+			///
+			/// The state of ManuallyDrop is bypassed, the value must not have been released and must not be released through the returned reference.
 			#[inline(always)]
 			pub unsafe fn as_mut_unsafestd_manuallydrop(&mut self) -> &mut UnsafeStdManuallyDrop<T> {
 				let $sself_as_mut_unsafestd_manuallydrop = self;
@@ -461,6 +911,47 @@ macro_rules! __codegen {
 						Some(self.state.read())
 					}
 
+					/// The place in the code where the value was released (dropped, taken, extracted
+					/// or forgotten) for the first time.
+					/// None - the value has not been released yet.
+					#[inline]
+					pub fn get_release_location(&self) -> Option<&'static ::core::panic::Location<'static>> {
+						self.state.read_location()
+					}
+
+					/// Manually drops the contained value, or returns the current state if the value
+					/// has already been released (the trigger is not executed).
+					#[track_caller]
+					#[inline]
+					pub fn try_drop(slot: &mut $current_type<T, Trig, S>) -> Result<(), ManuallyDropError> {
+						slot.state.try_to_indropmode()?;
+
+						slot.state.__in_drop(|| unsafe { // the state is checked, the value has not been released
+							UnsafeStdManuallyDrop::drop(&mut slot.value)
+						});
+						Self::__poison_released(slot);
+
+						Ok(())
+					}
+
+					/// Get reference to value, or the current state if the value has
+					/// already been released (the trigger is not executed).
+					#[inline]
+					pub fn try_as_value(&self) -> Result<&T, ManuallyDropError> {
+						self.state.try_deref()?;
+
+						Ok(unsafe { self.force_as_value() })
+					}
+
+					/// Get a mutable reference to a value, or the current state if the value has
+					/// already been released (the trigger is not executed).
+					#[inline]
+					pub fn try_as_mut_value(&mut self) -> Result<&mut T, ManuallyDropError> {
+						self.state.try_deref()?;
+
+						Ok(unsafe { self.force_as_mut_value() })
+					}
+
 					/// Checking if a trigger that defines undefined behavior will fire.
 					/// Some(true) - means that the state is empty and you can work with the value later.
 					/// Some(false) means that the value has already been converted by some method, and
@@ -474,9 +965,14 @@ macro_rules! __codegen {
 					}
 
 					/// Resets the ManuallyDrop state to its original state and returns the previous state.
+					///
+					/// # Safety
+					///
+					/// The checks of the previous state are lost, the value must be valid (not released, or written again) before ManuallyDrop is used.
 					#[inline]
 					pub unsafe fn get_state_and_reset(&self) -> Option<StateManuallyDropData> {
 						// Safe
+						self.__unpoison_released();
 						Some(self.state.get_and_reset())
 					}
 
@@ -496,6 +992,13 @@ macro_rules! __codegen {
 					pub fn is_next_trig_optionresult(&self) -> Option<bool> {
 						Some(self.state.is_next_trig())
 					}
+
+					/// Whether the destructor of the value panicked during drop, the value was
+					/// partly dropped and must not be used (the state is poisoned).
+					#[inline]
+					pub fn is_poisoned(&self) -> bool {
+						self.state.is_poisoned()
+					}
 				} else {
 					/// Get current state
 					/// !!!(Not supported in the unsafe version, always returns None).
@@ -504,6 +1007,51 @@ macro_rules! __codegen {
 						None
 					}
 
+					/// The place in the code where the value was released (dropped, taken, extracted
+					/// or forgotten) for the first time.
+					/// !!!(Not supported in the unsafe version, always returns None).
+					#[inline]
+					pub const fn get_release_location(&self) -> Option<&'static ::core::panic::Location<'static>> {
+						None
+					}
+
+					/// Manually drops the contained value, or returns the current state if the value
+					/// has already been released (the trigger is not executed).
+					/// !!!(The unsafe version is stateless and always drops the value, as UnsafeStdManuallyDrop::drop.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn try_drop(slot: &mut $current_type<T, Trig, S>) -> Result<(), ManuallyDropError> {
+						UnsafeStdManuallyDrop::drop(&mut slot.value);
+						Ok(())
+					}
+
+					/// Get reference to value, or the current state if the value has
+					/// already been released (the trigger is not executed).
+					/// !!!(The unsafe version is stateless and always returns the value.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn try_as_value(&self) -> Result<&T, ManuallyDropError> {
+						Ok(self.force_as_value())
+					}
+
+					/// Get a mutable reference to a value, or the current state if the value has
+					/// already been released (the trigger is not executed).
+					/// !!!(The unsafe version is stateless and always returns the value.)
+					///
+					/// # Safety
+					///
+					/// The value must not have been released (drop, take), the unsafe version does not check it.
+					#[inline]
+					pub unsafe fn try_as_mut_value(&mut self) -> Result<&mut T, ManuallyDropError> {
+						Ok(self.force_as_mut_value())
+					}
+
 					/// Checking if a trigger that defines undefined behavior will fire.
 					/// Some(true) - means that the state is empty and you can work with the value later.
 					/// Some(false) means that the value has already been converted by some method, and
@@ -518,6 +1066,10 @@ macro_rules! __codegen {
 
 					/// Resets the ManuallyDrop state to its original state and returns the previous state.
 					/// !!!(Not supported in the unsafe version, always returns None).
+					///
+					/// # Safety
+					///
+					/// The checks of the previous state are lost, the value must be valid (not released, or written again) before ManuallyDrop is used.
 					#[inline(always)]
 					pub const unsafe fn get_state_and_reset(&self) -> Option<StateManuallyDropData> {
 						None
@@ -541,10 +1093,33 @@ macro_rules! __codegen {
 					pub const fn is_next_trig_optionresult(&self) -> Option<bool> {
 						None
 					}
+
+					/// Whether the destructor of the value panicked during drop, the value was
+					/// partly dropped and must not be used (the state is poisoned).
+					/// !!!(Not supported in the unsafe version, always returns false).
+					#[inline(always)]
+					pub const fn is_poisoned(&self) -> bool {
+						false
+					}
+
+					/// Forget the state of ManuallyDrop, the API of `TransparentSafeManuallyDrop::forget_state`.
+					/// !!!(Not supported in the unsafe version, the state is not stored, does nothing).
+					///
+					/// # Safety
+					///
+					/// The value must not be accessed again until it is reinitialized (see `reinit`).
+					#[inline(always)]
+					pub const unsafe fn forget_state(slot: &$current_type<T, Trig, S>) {
+						let _slot = slot;
+					}
 				}
 			}
 
 			/// Get a raw pointer to a value. The call is always insecure.
+			///
+			/// # Safety
+			///
+			/// The value must not have been released (drop, take), the pointer is never checked.
 			#[inline(always)]
 			pub /*const*/ unsafe fn force_as_ptr(&self) -> *const T {
 				// TODO, VALID?, Exp: ManuallyDrop::as_ptr
@@ -552,6 +1127,10 @@ macro_rules! __codegen {
 			}
 
 			/// Get a raw mut pointer to a value. The call is always insecure.
+			///
+			/// # Safety
+			///
+			/// The value must not have been released (drop, take), the pointer is never checked.
 			#[inline(always)]
 			pub /*const*/ unsafe fn force_as_mut_ptr(&mut self) -> *mut T {
 				// TODO, VALID?, Exp: ManuallyDrop::as_mut_ptr
@@ -559,6 +1138,10 @@ macro_rules! __codegen {
 			}
 
 			/// Get reference to value. Always unprotected!
+			///
+			/// # Safety
+			///
+			/// The value must not have been released (drop, take), the access is never checked.
 			#[inline(always)]
 			pub /*const*/ unsafe fn force_as_value(&self) -> &T {
 				let $sself_force_as_value = self;
@@ -567,6 +1150,10 @@ macro_rules! __codegen {
 			}
 
 			/// Get a mutable reference to a value. Always unprotected!
+			///
+			/// # Safety
+			///
+			/// The value must not have been released (drop, take), the access is never checked.
 			#[inline(always)]
 			pub /*const*/ unsafe fn force_as_mut_value(&mut self) -> &mut T {
 				let $sself_force_as_mut_value = self;
@@ -584,7 +1171,8 @@ macro_rules! __codegen {
 		// #[derive(/*Copy, */Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]:
 		//
 
-		impl<T, Trig> Clone for $current_type<T, Trig> where T: Sized + Clone, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> Clone for $current_type<T, Trig, S> where T: Sized + Clone, Trig: TrigManuallyDrop, S: StateStorage {
+			#[track_caller]
 			#[inline]
 			fn clone(&self) -> Self {
 				let value = Clone::clone(self as &T);
@@ -593,9 +1181,10 @@ macro_rules! __codegen {
 			}
 		}
 
-		impl<T, Trig> Deref for $current_type<T, Trig> where T: ?Sized, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> Deref for $current_type<T, Trig, S> where T: ?Sized, Trig: TrigManuallyDrop, S: StateStorage {
 			type Target = T;
 
+			#[track_caller]
 			#[inline(always)]
 			fn deref(&self) -> &T {
 				#[allow(unused_unsafe)]
@@ -605,7 +1194,8 @@ macro_rules! __codegen {
 			}
 		}
 
-		impl<T, Trig> DerefMut for $current_type<T, Trig> where T: ?Sized, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> DerefMut for $current_type<T, Trig, S> where T: ?Sized, Trig: TrigManuallyDrop, S: StateStorage {
+			#[track_caller]
 			#[inline(always)]
 			fn deref_mut(&mut self) -> &mut T {
 				#[allow(unused_unsafe)]
@@ -615,14 +1205,15 @@ macro_rules! __codegen {
 			}
 		}
 
-		impl<T, Trig> Default for $current_type<T, Trig> where T: Sized + Default, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> Default for $current_type<T, Trig, S> where T: Sized + Default, Trig: TrigManuallyDrop, S: StateStorage {
+			#[track_caller]
 			#[inline(always)]
 			fn default() -> Self {
 				Self::new(Default::default())
 			}
 		}
 
-		impl<T, Trig> PartialEq<Self> for $current_type<T, Trig> where T: PartialEq<T>, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> PartialEq<Self> for $current_type<T, Trig, S> where T: PartialEq<T>, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn eq(&self, a: &Self) -> bool {
 				PartialEq::eq(self as &T, a)
@@ -636,7 +1227,7 @@ macro_rules! __codegen {
 			}
 		}
 
-		impl<T, Trig> PartialEq<T> for $current_type<T, Trig> where T: PartialEq<T>, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> PartialEq<T> for $current_type<T, Trig, S> where T: PartialEq<T>, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn eq(&self, a: &T) -> bool {
 				PartialEq::eq(self as &T, a)
@@ -650,7 +1241,7 @@ macro_rules! __codegen {
 			}
 		}
 
-		impl<T, Trig> PartialEq<UnsafeStdManuallyDrop<T>> for $current_type<T, Trig> where T: PartialEq<T>, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> PartialEq<UnsafeStdManuallyDrop<T>> for $current_type<T, Trig, S> where T: PartialEq<T>, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn eq(&self, a: &UnsafeStdManuallyDrop<T>) -> bool {
 				PartialEq::eq(self as &T, a)
@@ -664,56 +1255,52 @@ macro_rules! __codegen {
 			}
 		}
 
-		impl<T, Trig> Eq for $current_type<T, Trig> where T: Eq + PartialEq<T>, Trig: TrigManuallyDrop {
-			#[inline]
-			fn assert_receiver_is_total_eq(&self) {
-				Eq::assert_receiver_is_total_eq(self as &T)
-			}
-		}
+		impl<T, Trig, S> Eq for $current_type<T, Trig, S> where T: Eq + PartialEq<T>, Trig: TrigManuallyDrop, S: StateStorage {}
 
-		impl<T, Trig> Ord for $current_type<T, Trig> where T: Eq + PartialOrd<T> + Ord, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> Ord for $current_type<T, Trig, S> where T: Eq + PartialOrd<T> + Ord, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn cmp(&self, a: &Self) -> core::cmp::Ordering {
 				Ord::cmp(self as &T, a)
 			}
 		}
 
-		impl<T, Trig> PartialOrd<Self> for $current_type<T, Trig> where T: PartialEq<T> + PartialOrd<T>, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> PartialOrd<Self> for $current_type<T, Trig, S> where T: PartialEq<T> + PartialOrd<T>, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn partial_cmp(&self, a: &Self) -> Option<core::cmp::Ordering> {
 				PartialOrd::partial_cmp(self as &T, a)
 			}
 		}
 
-		impl<T, Trig> PartialOrd<T> for $current_type<T, Trig> where T: PartialEq<T> + PartialOrd<T>, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> PartialOrd<T> for $current_type<T, Trig, S> where T: PartialEq<T> + PartialOrd<T>, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn partial_cmp(&self, a: &T) -> Option<core::cmp::Ordering> {
 				PartialOrd::partial_cmp(self as &T, a)
 			}
 		}
 
-		impl<T, Trig> PartialOrd<UnsafeStdManuallyDrop<T>> for $current_type<T, Trig> where T: PartialEq<T> + PartialOrd<T>, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> PartialOrd<UnsafeStdManuallyDrop<T>> for $current_type<T, Trig, S> where T: PartialEq<T> + PartialOrd<T>, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn partial_cmp(&self, a: &UnsafeStdManuallyDrop<T>) -> Option<core::cmp::Ordering> {
 				PartialOrd::partial_cmp(self as &T, a)
 			}
 		}
 
-		impl<T, Trig> Hash for $current_type<T, Trig> where T: Hash, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> Hash for $current_type<T, Trig, S> where T: Hash, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn hash<H>(&self, a: &mut H) where H: core::hash::Hasher {
 				Hash::hash(self as &T, a)
 			}
 		}
 
-		impl<T, Trig> Debug for $current_type<T, Trig> where T: Debug, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> Debug for $current_type<T, Trig, S> where T: Debug, Trig: TrigManuallyDrop, S: StateStorage {
 			#[inline]
 			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
 				Debug::fmt(self as &T, f)
 			}
 		}
 
-		impl<T, Trig> From<T> for $current_type<T, Trig> where T: Sized, Trig: TrigManuallyDrop {
+		impl<T, Trig, S> From<T> for $current_type<T, Trig, S> where T: Sized, Trig: TrigManuallyDrop, S: StateStorage {
+			#[track_caller]
 			#[inline(always)]
 			fn from(a: T) -> Self {
 				Self::new(a)
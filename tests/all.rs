@@ -245,3 +245,33 @@ fn test_thread_drop() {
 	let ok = is_ok.unwrap();
 	assert!(ok);
 }
+
+#[cfg(feature = "support_panic_trig")]
+#[test]
+fn test_try_api() {
+	use SafeManuallyDrop::core::state::StateManuallyDropData;
+	use SafeManuallyDrop::AlwaysSafePanicManuallyDrop as PanicManuallyDrop;
+
+	let mut data = PanicManuallyDrop::new(build_new_test_vec());
+	assert_eq!(data.try_as_value().unwrap(), &build_new_test_vec());
+
+	let value = PanicManuallyDrop::try_take(&mut data).unwrap();
+	assert_eq!(value, build_new_test_vec());
+
+	// the trigger is not executed, the state remains the same
+	let e = PanicManuallyDrop::try_take(&mut data).unwrap_err();
+	assert_eq!(e.state(), StateManuallyDropData::TakeModeTrig);
+	let e = PanicManuallyDrop::try_drop(&mut data).unwrap_err();
+	assert_eq!(e.state(), StateManuallyDropData::TakeModeTrig);
+	assert!(data.try_as_mut_value().is_err());
+
+	let (data, e) = PanicManuallyDrop::try_into_inner(data).unwrap_err();
+	assert_eq!(e.state(), StateManuallyDropData::TakeModeTrig);
+	assert_eq!(data.get_state(), Some(StateManuallyDropData::TakeModeTrig));
+
+	let data = PanicManuallyDrop::new(build_new_test_vec());
+	assert_eq!(
+		PanicManuallyDrop::try_into_inner(data).unwrap(),
+		build_new_test_vec()
+	);
+}
@@ -0,0 +1,108 @@
+#![cfg(safemanuallydrop_asan)]
+
+//! RUSTFLAGS="-Zsanitizer=address" cargo +nightly test --target x86_64-unknown-linux-gnu --test asan
+//! (the `asan` job of the CI, the test is empty without AddressSanitizer)
+//!
+//! Only the types that opt in (`WithPoisonFill`) are poisoned, their released slots
+//! are kept in place (or reinitialized before they are moved), the released slots
+//! of the other types are moved freely.
+
+use core::ffi::c_void;
+use SafeManuallyDrop::core::poison::WithPoisonFill;
+use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+
+type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, WithPoisonFill<EmptyLoopTrigManuallyDrop>>;
+type DefManuallyDrop<T> = AlwaysSafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+extern "C" {
+	fn __asan_address_is_poisoned(addr: *const c_void) -> i32;
+}
+
+fn is_poisoned<T>(ptr: *const T) -> bool {
+	unsafe { __asan_address_is_poisoned(ptr as *const c_void) != 0 }
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_asan_poison() {
+	let mut slot = Box::new(ManuallyDrop::new([1u64, 2, 3, 4]));
+	let ptr = unsafe { slot.as_ptr() };
+	assert!(!is_poisoned(ptr));
+
+	unsafe {
+		ManuallyDrop::drop(&mut slot);
+	}
+	assert!(is_poisoned(ptr));
+
+	ManuallyDrop::reinit(&mut slot, [5, 6, 7, 8]);
+	assert!(!is_poisoned(ptr));
+	assert_eq!(*slot, [5, 6, 7, 8]);
+
+	assert_eq!(unsafe { ManuallyDrop::take(&mut slot) }, [5, 6, 7, 8]);
+	assert!(is_poisoned(ptr));
+
+	// destroyed, the memory is unpoisoned before it is freed
+	drop(slot);
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_asan_unpoison_on_destruction() {
+	for i in 0..2u64 {
+		// the released slot is destroyed in place at the end of the iteration,
+		// the next slot reuses its stack memory
+		let mut slot = ManuallyDrop::new([i; 4]);
+		assert!(!is_poisoned(unsafe { slot.as_ptr() }));
+		assert_eq!(*slot, [i; 4]);
+
+		unsafe {
+			ManuallyDrop::drop(&mut slot);
+		}
+		assert!(is_poisoned(unsafe { slot.force_as_ptr() }));
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_asan_move_after_reinit() {
+	let mut slots = Vec::with_capacity(1);
+	slots.push(ManuallyDrop::new([1u64, 2]));
+	unsafe {
+		ManuallyDrop::drop(&mut slots[0]);
+	}
+
+	// the released slot must not be moved before it is reinitialized
+	ManuallyDrop::reinit(&mut slots[0], [3, 4]);
+	slots.push(ManuallyDrop::new([5, 6])); // <-- the slots are moved
+	assert_eq!(*slots[0], [3, 4]);
+
+	for mut slot in slots {
+		unsafe {
+			ManuallyDrop::drop(&mut slot);
+		}
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_asan_not_opted_in_is_moved() {
+	let mut slots = Vec::with_capacity(1);
+	slots.push(DefManuallyDrop::new([1u64, 2]));
+	unsafe {
+		DefManuallyDrop::drop(&mut slots[0]);
+	}
+	assert!(!is_poisoned(unsafe { slots[0].force_as_ptr() }));
+
+	// the released slot is moved before it is reinitialized, nothing is reported
+	slots.push(DefManuallyDrop::new([3, 4])); // <-- the slots are moved
+	assert!(slots[0].is_next_trig());
+	DefManuallyDrop::reinit(&mut slots[0], [5, 6]);
+	assert_eq!(*slots[0], [5, 6]);
+
+	for mut slot in slots {
+		unsafe {
+			DefManuallyDrop::drop(&mut slot);
+		}
+	}
+}
@@ -0,0 +1,38 @@
+use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+use SafeManuallyDrop::AlwaysSafeCellManuallyDrop;
+
+type ManuallyDrop<T> = AlwaysSafeCellManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_cell_manuallydrop() {
+	let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+	assert_eq!(data.len(), 3);
+	data.push(4);
+
+	let value = unsafe { ManuallyDrop::take(&mut data) };
+	assert_eq!(value, [1, 2, 3, 4]);
+	assert!(data.try_as_value().is_err());
+
+	let mut data = SafeManuallyDrop::CellManuallyDrop::new(vec![1, 2, 3]);
+	unsafe {
+		SafeManuallyDrop::CellManuallyDrop::drop(&mut data);
+	}
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[allow(unused_unsafe)]
+fn test_cell_violation() {
+	use SafeManuallyDrop::core::report::ViolationKind;
+	use SafeManuallyDrop::core::testing::expect_violation;
+
+	let report = expect_violation(|| {
+		let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+		unsafe {
+			ManuallyDrop::drop(&mut data);
+			ManuallyDrop::drop(&mut data);
+		}
+	});
+	assert_eq!(report.kind(), ViolationKind::DoubleDrop);
+}
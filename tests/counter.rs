@@ -0,0 +1,124 @@
+#![cfg(feature = "support_count_trig")]
+
+use SafeManuallyDrop::core::report::ViolationKind;
+use SafeManuallyDrop::core::trig::counter::{
+	AlwaysSafeCounterManuallyDrop, CounterDomain, CounterSet, CounterSnapshot,
+	CounterTrigManuallyDrop,
+};
+use SafeManuallyDrop::core::trig::TrigManuallyDrop;
+
+enum KindsDomain {}
+
+impl CounterDomain for KindsDomain {
+	fn counters() -> &'static CounterSet {
+		static COUNTERS: CounterSet = CounterSet::new();
+
+		&COUNTERS
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_counter_kinds() {
+	type ManuallyDrop<T> = AlwaysSafeCounterManuallyDrop<T, KindsDomain>;
+	let counters = KindsDomain::counters();
+
+	let mut dropped = ManuallyDrop::new(vec![1, 2, 3]);
+	unsafe {
+		ManuallyDrop::drop(&mut dropped);
+		ManuallyDrop::drop(&mut dropped); // double drop
+		ManuallyDrop::drop(&mut dropped); // double drop
+	}
+	assert!(ManuallyDrop::take_or_none(&mut dropped).is_none()); // take after drop
+
+	let mut taken = ManuallyDrop::new(vec![4, 5, 6]);
+	assert_eq!(ManuallyDrop::take_or_none(&mut taken), Some(vec![4, 5, 6]));
+	assert!(ManuallyDrop::take_or_none(&mut taken).is_none()); // double take
+	unsafe {
+		ManuallyDrop::drop(&mut taken); // drop after take
+	}
+
+	// without a violation report
+	CounterTrigManuallyDrop::<KindsDomain>::trig_next_invalid_beh(format_args!("manual"));
+
+	let snapshot = counters.snapshot();
+	assert_eq!(snapshot.get(ViolationKind::DoubleDrop), 2);
+	assert_eq!(snapshot.get(ViolationKind::TakeAfterDrop), 1);
+	assert_eq!(snapshot.get(ViolationKind::DoubleTake), 1);
+	assert_eq!(snapshot.get(ViolationKind::DropAfterTake), 1);
+	assert_eq!(snapshot.get(ViolationKind::UseAfterDrop), 0);
+	assert_eq!(snapshot.other(), 1);
+	assert_eq!(snapshot.total(), 6);
+	assert_eq!(counters.get_count_trig_events(), 6);
+
+	let nonzero = snapshot
+		.iter()
+		.filter(|(_kind, count)| *count != 0)
+		.collect::<Vec<_>>();
+	assert_eq!(
+		nonzero,
+		[
+			(ViolationKind::DoubleDrop, 2),
+			(ViolationKind::DropAfterTake, 1),
+			(ViolationKind::TakeAfterDrop, 1),
+			(ViolationKind::DoubleTake, 1),
+		]
+	);
+	// the unknown violations are counted as other
+	assert_eq!(snapshot.iter().count(), ViolationKind::COUNT - 1);
+
+	// the deltas are returned once
+	assert_eq!(counters.swap_and_reset(), snapshot);
+	assert_eq!(counters.snapshot(), CounterSnapshot::default());
+	assert_eq!(counters.swap_and_reset().total(), 0);
+}
+
+enum DomainA {}
+
+impl CounterDomain for DomainA {
+	fn counters() -> &'static CounterSet {
+		static COUNTERS: CounterSet = CounterSet::new();
+
+		&COUNTERS
+	}
+}
+
+enum DomainB {}
+
+impl CounterDomain for DomainB {
+	fn counters() -> &'static CounterSet {
+		static COUNTERS: CounterSet = CounterSet::new();
+
+		&COUNTERS
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_counter_domains() {
+	let mut a = AlwaysSafeCounterManuallyDrop::<_, DomainA>::new(1);
+	let b = AlwaysSafeCounterManuallyDrop::<_, DomainB>::new(2);
+	unsafe {
+		AlwaysSafeCounterManuallyDrop::drop(&mut a);
+		AlwaysSafeCounterManuallyDrop::drop(&mut a);
+	}
+	CounterTrigManuallyDrop::<DomainA>::trig_next_invalid_beh(format_args!("manual"));
+
+	assert_eq!(
+		DomainA::counters()
+			.snapshot()
+			.get(ViolationKind::DoubleDrop),
+		1
+	);
+	assert_eq!(DomainA::counters().get_count_trig_events(), 2);
+
+	// the violations in the domain A are not counted in the domain B
+	assert_eq!(DomainB::counters().snapshot(), CounterSnapshot::default());
+	assert_eq!(
+		AlwaysSafeCounterManuallyDrop::<(), DomainB>::get_count_trig_events(),
+		0
+	);
+
+	let _b = AlwaysSafeCounterManuallyDrop::into_inner(b);
+	assert_eq!(DomainB::counters().get_count_trig_events(), 0);
+}
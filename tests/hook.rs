@@ -0,0 +1,69 @@
+#![cfg(all(feature = "std", feature = "support_hookfn_trig"))]
+
+use std::panic::catch_unwind;
+use SafeManuallyDrop::core::trig::hook;
+use SafeManuallyDrop::AlwaysSafeHookManuallyDrop as ManuallyDrop;
+
+/// Execute the function, it must panic, return the panic message.
+fn panic_message(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+	let e = catch_unwind(f).unwrap_err();
+
+	match e.downcast::<String>() {
+		Ok(str) => *str,
+		Err(e) => e.downcast_ref::<&str>().unwrap().to_string(),
+	}
+}
+
+#[allow(unused_unsafe)]
+fn double_drop() {
+	let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+	unsafe {
+		ManuallyDrop::drop(&mut data);
+		ManuallyDrop::drop(&mut data);
+	}
+}
+
+#[test]
+fn test_with_hook() {
+	let message = hook::with_hook(
+		|args| panic!("scoped hook: {}", args),
+		|| panic_message(double_drop),
+	);
+	assert!(message.starts_with("scoped hook: "));
+
+	// the hook of the thread has been restored
+	let message = panic_message(double_drop);
+	assert!(!message.starts_with("scoped hook: "));
+}
+
+#[test]
+fn test_with_hook_nested_unwind() {
+	let message = hook::with_hook(
+		|args| panic!("outer hook: {}", args),
+		|| {
+			let e = catch_unwind(|| {
+				hook::with_hook(|args| panic!("inner hook: {}", args), || panic!("unwind"))
+			});
+			assert!(e.is_err());
+
+			// the outer hook has been restored after unwinding
+			panic_message(double_drop)
+		},
+	);
+	assert!(message.starts_with("outer hook: "));
+}
+
+#[test]
+fn test_with_hook_other_thread() {
+	hook::with_hook(
+		|args| panic!("scoped hook: {}", args),
+		|| {
+			let message = std::thread::spawn(|| panic_message(double_drop))
+				.join()
+				.unwrap();
+
+			// other threads use the global hook
+			assert!(!message.starts_with("scoped hook: "));
+		},
+	);
+}
@@ -0,0 +1,96 @@
+#![cfg(all(feature = "alloc", feature = "support_hookfn_trig"))]
+
+use std::panic::catch_unwind;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use SafeManuallyDrop::core::report::ViolationKind;
+use SafeManuallyDrop::core::trig::hook;
+use SafeManuallyDrop::AlwaysSafeHookManuallyDrop as ManuallyDrop;
+
+/// The extended hook is global, the tests must not run in parallel.
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[allow(unused_unsafe)]
+fn double_drop() {
+	let mut data = ManuallyDrop::new([1, 2, 3]);
+	unsafe {
+		ManuallyDrop::drop(&mut data);
+		ManuallyDrop::drop(&mut data);
+	}
+}
+
+#[test]
+fn test_boxed_hook() {
+	let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+	let kinds = Arc::new(Mutex::new(Vec::new()));
+	let old_hook = hook::set_hook_boxed({
+		let kinds = kinds.clone();
+
+		Box::new(move |report| kinds.lock().unwrap().push(report.kind()))
+	});
+	assert!(old_hook.is_none());
+
+	let _e = catch_unwind(double_drop);
+	assert_eq!(&*kinds.lock().unwrap(), &[ViolationKind::DoubleDrop]);
+
+	assert!(hook::take_extended_hook().is_some());
+	assert!(hook::get_extended_hook().is_none());
+}
+
+#[test]
+fn test_context_hook() {
+	let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+	static COUNTER: AtomicUsize = AtomicUsize::new(0);
+	hook::set_hook_with_context(
+		|context, args| {
+			let counter = unsafe { &*(context as *const AtomicUsize) };
+			counter.fetch_add(1, Ordering::SeqCst);
+
+			panic!("{}", args);
+		},
+		&COUNTER,
+	);
+
+	let e = catch_unwind(double_drop);
+	assert!(e.is_err());
+	assert_eq!(COUNTER.load(Ordering::SeqCst), 1);
+
+	assert!(hook::take_extended_hook().is_some());
+}
+
+#[test]
+fn test_extended_hook_arguments() {
+	let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+	let messages = Arc::new(Mutex::new(Vec::new()));
+	hook::set_hook_boxed({
+		let messages = messages.clone();
+
+		Box::new(move |report| {
+			assert_eq!(report.kind(), ViolationKind::Unknown);
+			assert_eq!(report.state(), None);
+			assert_eq!(report.operation(), None);
+			messages.lock().unwrap().push(report.to_string());
+		})
+	});
+
+	// a violation reported with only a message also reaches the extended hook
+	let e = catch_unwind(|| hook::run_hook(format_args!("direct {}", 1)));
+	assert!(e.is_err());
+	assert_eq!(messages.lock().unwrap().len(), 1);
+	assert!(messages.lock().unwrap()[0].starts_with("direct 1"));
+
+	// the hook function replaces the extended hook
+	fn panic_hook(args: std::fmt::Arguments<'_>) -> ! {
+		hook::default_hook(args)
+	}
+	hook::set_hook(panic_hook);
+	assert!(hook::get_extended_hook().is_none());
+
+	let _e = catch_unwind(double_drop);
+	assert_eq!(messages.lock().unwrap().len(), 1);
+
+	hook::take_hook();
+}
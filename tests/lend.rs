@@ -0,0 +1,114 @@
+use SafeManuallyDrop::core::state::StateManuallyDropData;
+use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+
+type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_lend() {
+	let mut slot = ManuallyDrop::new(vec![1, 2, 3]);
+
+	{
+		let mut lent = ManuallyDrop::lend(&mut slot);
+		lent.push(4);
+		assert_eq!(*lent, [1, 2, 3, 4]);
+	}
+	assert_eq!(slot.get_state(), Some(StateManuallyDropData::Empty));
+	assert_eq!(slot.get_release_location(), None);
+	assert_eq!(*slot, [1, 2, 3, 4]);
+
+	let len = ManuallyDrop::with_taken(&mut slot, |mut value| {
+		value.push(5);
+		let len = value.len();
+
+		(value, len)
+	});
+	assert_eq!(len, 5);
+	assert_eq!(slot.get_state(), Some(StateManuallyDropData::Empty));
+	assert_eq!(*slot, [1, 2, 3, 4, 5]);
+
+	unsafe {
+		ManuallyDrop::drop(&mut slot);
+	}
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[allow(unused_unsafe)]
+fn test_lend_unwind() {
+	use std::panic::{catch_unwind, AssertUnwindSafe};
+
+	let mut slot = ManuallyDrop::new(vec![1, 2, 3]);
+
+	// the guard puts the value back during unwinding
+	let result = catch_unwind(AssertUnwindSafe(|| {
+		let mut lent = ManuallyDrop::lend(&mut slot);
+		lent.push(4);
+
+		panic!("lend");
+	}));
+	assert!(result.is_err());
+	assert_eq!(slot.get_state(), Some(StateManuallyDropData::Empty));
+	assert_eq!(*slot, [1, 2, 3, 4]);
+
+	// the value was moved into the function, it is lost
+	let result = catch_unwind(AssertUnwindSafe(|| {
+		ManuallyDrop::with_taken(&mut slot, |_value| -> (Vec<i32>, ()) {
+			panic!("with_taken")
+		})
+	}));
+	assert!(result.is_err());
+	assert_eq!(slot.get_state(), Some(StateManuallyDropData::TakeModeTrig));
+
+	ManuallyDrop::reinit(&mut slot, vec![5]);
+	unsafe {
+		ManuallyDrop::drop(&mut slot);
+	}
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[allow(unused_unsafe)]
+fn test_lend_violation() {
+	use SafeManuallyDrop::core::report::{ManuallyDropOperation, ViolationKind};
+	use SafeManuallyDrop::core::testing::expect_violation;
+
+	// the guard was forgotten, the value is never put back
+	let report = expect_violation(|| {
+		let mut slot = ManuallyDrop::new([1, 2, 3]);
+		core::mem::forget(ManuallyDrop::lend(&mut slot));
+
+		let _len = slot.len();
+	});
+	assert_eq!(report.kind(), ViolationKind::UseWhileLent);
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Deref));
+	assert!(report.to_string().contains("is lent (lend, with_taken)"));
+
+	let report = expect_violation(|| {
+		let mut slot = ManuallyDrop::new([1, 2, 3]);
+		core::mem::forget(ManuallyDrop::lend(&mut slot));
+
+		ManuallyDrop::reinit(&mut slot, [4, 5, 6]);
+	});
+	assert_eq!(report.kind(), ViolationKind::UseWhileLent);
+
+	let report = expect_violation(|| {
+		let mut slot = ManuallyDrop::new([1, 2, 3]);
+		core::mem::forget(ManuallyDrop::lend(&mut slot));
+
+		let _value = ManuallyDrop::get_or_reinit(&mut slot, || [4, 5, 6]);
+	});
+	assert_eq!(report.kind(), ViolationKind::UseWhileLent);
+
+	let report = expect_violation(|| {
+		let mut slot = ManuallyDrop::new([1, 2, 3]);
+		unsafe {
+			ManuallyDrop::drop(&mut slot);
+		}
+
+		let _lent = ManuallyDrop::lend(&mut slot);
+	});
+	assert_eq!(report.kind(), ViolationKind::TakeAfterDrop);
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Lend));
+}
@@ -0,0 +1,57 @@
+#![cfg(all(feature = "support_panic_trig", feature = "track_location"))]
+
+use SafeManuallyDrop::AlwaysSafePanicManuallyDrop as PanicManuallyDrop;
+
+/// Extract the text of the panic message
+fn panic_message(e: Box<dyn std::any::Any + Send>) -> String {
+	match e.downcast::<String>() {
+		Ok(a) => *a,
+		Err(e) => (*e.downcast::<&'static str>().unwrap()).into(),
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_combo_drop_location() {
+	let mut data = PanicManuallyDrop::new(vec![1, 2, 3]);
+
+	let first_line = line!() + 1;
+	unsafe { PanicManuallyDrop::drop(&mut data) };
+	assert_eq!(data.get_release_location().unwrap().line(), first_line);
+
+	let again_line = line!() + 2;
+	let e = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+		PanicManuallyDrop::drop(&mut data)
+	}))
+	.unwrap_err();
+
+	let message = panic_message(e);
+	assert!(message.contains(&format!(
+		"first dropped at {}:{}, used again at {}:{}",
+		file!(),
+		first_line,
+		file!(),
+		again_line
+	)));
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_deref_location() {
+	let mut data = PanicManuallyDrop::new(vec![1, 2, 3]);
+
+	let first_line = line!() + 1;
+	let _value = unsafe { PanicManuallyDrop::take(&mut data) };
+
+	let again_line = line!() + 1;
+	let e = std::panic::catch_unwind(|| data.len()).unwrap_err();
+
+	let message = panic_message(e);
+	assert!(message.contains(&format!(
+		"first taken at {}:{}, used again at {}:{}",
+		file!(),
+		first_line,
+		file!(),
+		again_line
+	)));
+}
@@ -0,0 +1,71 @@
+// (under AddressSanitizer, the reads of the released values are reported, see tests/asan.rs)
+#![cfg(not(safemanuallydrop_asan))]
+
+use SafeManuallyDrop::core::poison::WithPoisonFill;
+use SafeManuallyDrop::core::poison::POISON_FILL_BYTE;
+use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+use SafeManuallyDrop::AlwaysUnsafeManuallyDrop;
+
+type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, WithPoisonFill<EmptyLoopTrigManuallyDrop>>;
+type DefManuallyDrop<T> = AlwaysSafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+type UnsafeManuallyDrop<T> = AlwaysUnsafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_poison_fill() {
+	// the released slots are kept in place
+	let mut a = Box::new(ManuallyDrop::new([1u8, 2, 3, 4]));
+	let ptr = unsafe { a.as_ptr() };
+	unsafe {
+		ManuallyDrop::drop(&mut a);
+	}
+	assert_eq!(unsafe { ptr.read() }, [POISON_FILL_BYTE; 4]);
+
+	let mut b = Box::new(ManuallyDrop::new(vec![1, 2, 3]));
+	let ptr = unsafe { b.as_ptr() } as *const u8;
+	assert_eq!(unsafe { ManuallyDrop::take(&mut b) }, [1, 2, 3]);
+	let bytes = unsafe { core::slice::from_raw_parts(ptr, core::mem::size_of::<Vec<i32>>()) };
+	assert!(bytes.iter().all(|a| *a == POISON_FILL_BYTE));
+
+	// the slot can be reused
+	ManuallyDrop::reinit(&mut b, vec![4]);
+	assert_eq!(**b, [4]);
+	unsafe {
+		ManuallyDrop::drop(&mut b);
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_unsafe_is_not_filled() {
+	let mut a = UnsafeManuallyDrop::new([1u8, 2, 3, 4]);
+	let ptr = unsafe { a.as_ptr() };
+	assert_eq!(unsafe { UnsafeManuallyDrop::take(&mut a) }, [1, 2, 3, 4]);
+	assert_eq!(unsafe { ptr.read() }, [1, 2, 3, 4]);
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_not_opted_in_is_not_filled() {
+	let mut slots = Vec::with_capacity(1);
+	slots.push(DefManuallyDrop::new(vec![1, 2, 3]));
+	let ptr = unsafe { slots[0].as_ptr() } as *const u8;
+	let before =
+		unsafe { core::slice::from_raw_parts(ptr, core::mem::size_of::<Vec<i32>>()) }.to_vec();
+	assert_eq!(unsafe { DefManuallyDrop::take(&mut slots[0]) }, [1, 2, 3]);
+	let after = unsafe { core::slice::from_raw_parts(ptr, core::mem::size_of::<Vec<i32>>()) };
+	assert_eq!(after, before);
+
+	// the released slot can be moved (the bytes are still a valid value)
+	slots.push(DefManuallyDrop::new(vec![4]));
+	assert!(slots[0].is_next_trig());
+	DefManuallyDrop::reinit(&mut slots[0], vec![5]);
+	assert_eq!(*slots[0], [5]);
+
+	for mut slot in slots {
+		unsafe {
+			DefManuallyDrop::drop(&mut slot);
+		}
+	}
+}
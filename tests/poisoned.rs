@@ -0,0 +1,81 @@
+#![cfg(feature = "std")]
+
+use std::cell::Cell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use SafeManuallyDrop::core::report::{ManuallyDropOperation, ViolationKind};
+use SafeManuallyDrop::core::state::StateManuallyDropData;
+use SafeManuallyDrop::core::testing::expect_violation;
+use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+
+type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+/// The destructor panics.
+struct PanicInDrop;
+
+impl Drop for PanicInDrop {
+	fn drop(&mut self) {
+		panic!("PanicInDrop");
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_poisoned() {
+	let mut slot = ManuallyDrop::new(PanicInDrop);
+	assert!(!slot.is_poisoned());
+
+	let result = catch_unwind(AssertUnwindSafe(|| unsafe {
+		ManuallyDrop::drop(&mut slot);
+	}));
+	assert!(result.is_err());
+	assert!(slot.is_poisoned());
+	assert_eq!(slot.get_state(), Some(StateManuallyDropData::Poisoned));
+
+	let report = expect_violation(|| unsafe {
+		ManuallyDrop::drop(&mut slot);
+	});
+	assert_eq!(report.kind(), ViolationKind::UseAfterPoisonedDrop);
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Drop));
+	assert!(report
+		.to_string()
+		.contains("the destructor of the value panicked"));
+
+	// the slot can be reused
+	ManuallyDrop::reinit(&mut slot, PanicInDrop);
+	assert!(!slot.is_poisoned());
+	unsafe {
+		slot.ignore_drop();
+	}
+}
+
+thread_local! {
+	static REENTER: Cell<*const ManuallyDrop<Reenter>> = const { Cell::new(core::ptr::null()) };
+}
+
+/// The destructor reads the ManuallyDrop that is dropping it.
+struct Reenter(u32);
+
+impl Drop for Reenter {
+	fn drop(&mut self) {
+		let slot = REENTER.with(|a| a.get());
+		let _value = unsafe { &*slot }.0;
+	}
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_reentrant_drop() {
+	let report = expect_violation(|| {
+		let mut slot = ManuallyDrop::new(Reenter(1));
+		REENTER.with(|a| a.set(&slot));
+
+		unsafe {
+			ManuallyDrop::drop(&mut slot);
+		}
+	});
+	assert_eq!(report.kind(), ViolationKind::UseDuringDrop);
+	assert_eq!(report.state(), Some(StateManuallyDropData::InDrop));
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Deref));
+	assert!(report.to_string().contains("(re-entrancy)"));
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use SafeManuallyDrop::core::report::ViolationKind;
+use SafeManuallyDrop::core::trig::record::{
+	AlwaysSafeRecordManuallyDrop, RecordBuffer, RecordDomain, RecordSlot,
+};
+
+enum ThreadsDomain {}
+
+impl RecordDomain for ThreadsDomain {
+	fn buffer() -> &'static RecordBuffer {
+		static BUFFER: RecordBuffer<[RecordSlot; 8]> = RecordBuffer::new();
+
+		&BUFFER
+	}
+}
+
+type ManuallyDrop<T> = AlwaysSafeRecordManuallyDrop<T, ThreadsDomain>;
+
+const VIOLATIONS: u64 = 2000;
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_record_drain_from_another_thread() {
+	let buffer = ThreadsDomain::buffer();
+	let done = Arc::new(AtomicBool::new(false));
+
+	// the writers overwrite the small ring while the reader drains it
+	let writers = [0, 1].map(|writer| {
+		thread::spawn(move || {
+			for _ in 0..VIOLATIONS / 2 {
+				if writer == 0 {
+					let mut data = ManuallyDrop::new(1u32);
+					unsafe {
+						ManuallyDrop::drop(&mut data);
+						ManuallyDrop::drop(&mut data); // double drop
+					}
+				} else {
+					let mut data = ManuallyDrop::new(String::from("a"));
+					let _value = ManuallyDrop::take_or_none(&mut data);
+					assert!(ManuallyDrop::take_or_none(&mut data).is_none()); // double take
+				}
+			}
+		})
+	});
+
+	let reader = thread::spawn({
+		let done = done.clone();
+
+		move || {
+			let mut seen = 0u64;
+			loop {
+				let is_done = done.load(Ordering::Acquire);
+				for record in buffer.iter().chain(buffer.drain()) {
+					// a record is never torn: the fields belong to the same violation
+					match record.kind() {
+						ViolationKind::DoubleDrop => {
+							assert_eq!(record.type_name(), std::any::type_name::<u32>())
+						}
+						ViolationKind::DoubleTake => {
+							assert_eq!(record.type_name(), std::any::type_name::<String>())
+						}
+						kind => panic!("unexpected record {:?}", kind),
+					}
+					assert_eq!(record.location().unwrap().file(), file!());
+					seen += 1;
+				}
+				if is_done {
+					break seen;
+				}
+			}
+		}
+	});
+
+	for writer in writers {
+		writer.join().unwrap();
+	}
+	done.store(true, Ordering::Release);
+	let seen = reader.join().unwrap();
+
+	assert_eq!(buffer.total(), VIOLATIONS);
+	assert!(seen > 0);
+	assert_eq!(buffer.drain().count(), 0);
+}
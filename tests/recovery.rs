@@ -0,0 +1,47 @@
+#![cfg(feature = "support_count_trig")]
+
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+use SafeManuallyDrop::core::report::ViolationKind;
+use SafeManuallyDrop::core::trig::counter::{
+	AlwaysSafeCounterManuallyDrop, CounterDomain, CounterSet,
+};
+
+enum RecoveryDomain {}
+
+impl CounterDomain for RecoveryDomain {
+	fn counters() -> &'static CounterSet {
+		static COUNTERS: CounterSet = CounterSet::new();
+
+		&COUNTERS
+	}
+}
+
+type ManuallyDrop<T> = AlwaysSafeCounterManuallyDrop<T, RecoveryDomain>;
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_recovery_mode() {
+	let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+	unsafe {
+		ManuallyDrop::drop(&mut data);
+		// skipped, no double free
+		ManuallyDrop::drop(&mut data);
+	}
+	assert_eq!(ManuallyDrop::take_or_none(&mut data), None);
+
+	// reading and taking the freed value is refused
+	let e = catch_unwind(AssertUnwindSafe(|| {
+		let _e = data.len();
+	}));
+	assert!(e.is_err());
+	let e = catch_unwind(AssertUnwindSafe(|| unsafe {
+		let _e = ManuallyDrop::take(&mut data);
+	}));
+	assert!(e.is_err());
+
+	let snapshot = RecoveryDomain::counters().snapshot();
+	assert_eq!(snapshot.get(ViolationKind::DoubleDrop), 1);
+	assert_eq!(snapshot.get(ViolationKind::TakeAfterDrop), 2);
+	assert_eq!(snapshot.get(ViolationKind::UseAfterDrop), 1);
+}
@@ -0,0 +1,63 @@
+use SafeManuallyDrop::core::state::StateManuallyDropData;
+use SafeManuallyDrop::core::trig::r#loop::EmptyLoopTrigManuallyDrop;
+use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+
+type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, EmptyLoopTrigManuallyDrop>;
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_reinit_slot() {
+	let mut slot = ManuallyDrop::new(vec![1, 2, 3]);
+
+	for i in 0..3 {
+		unsafe {
+			ManuallyDrop::drop(&mut slot);
+		}
+		assert_eq!(slot.get_state(), Some(StateManuallyDropData::DropModeTrig));
+
+		ManuallyDrop::reinit(&mut slot, vec![i]);
+		assert_eq!(slot.get_state(), Some(StateManuallyDropData::Empty));
+		assert_eq!(slot.get_release_location(), None);
+		assert_eq!(*slot, [i]);
+	}
+
+	assert_eq!(ManuallyDrop::replace(&mut slot, vec![4]), [2]);
+	assert_eq!(slot.get_state(), Some(StateManuallyDropData::Empty));
+
+	let value = unsafe { ManuallyDrop::take(&mut slot) };
+	assert_eq!(value, [4]);
+	assert_eq!(ManuallyDrop::get_or_reinit(&mut slot, || vec![5]), &[5]);
+	assert_eq!(
+		ManuallyDrop::get_or_reinit(&mut slot, || unreachable!()),
+		&[5]
+	);
+
+	unsafe {
+		ManuallyDrop::drop(&mut slot);
+	}
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[allow(unused_unsafe)]
+fn test_reinit_violation() {
+	use SafeManuallyDrop::core::report::{ManuallyDropOperation, ViolationKind};
+	use SafeManuallyDrop::core::testing::expect_violation;
+
+	// the value has not been released, it would be leaked
+	let report = expect_violation(|| {
+		let mut slot = ManuallyDrop::new(vec![1, 2, 3]);
+		ManuallyDrop::reinit(&mut slot, vec![4]);
+	});
+	assert_eq!(report.kind(), ViolationKind::ReinitWithoutRelease);
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Reinit));
+
+	let report = expect_violation(|| {
+		let mut slot = ManuallyDrop::new(vec![1, 2, 3]);
+		unsafe {
+			ManuallyDrop::drop(&mut slot);
+		}
+		let _old = ManuallyDrop::replace(&mut slot, vec![4]);
+	});
+	assert_eq!(report.kind(), ViolationKind::TakeAfterDrop);
+}
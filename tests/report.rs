@@ -0,0 +1,135 @@
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::fmt::Arguments;
+use SafeManuallyDrop::core::report::{ManuallyDropOperation, ViolationKind, ViolationReport};
+use SafeManuallyDrop::core::state::StateManuallyDropData;
+use SafeManuallyDrop::core::trig::TrigManuallyDrop;
+use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+
+thread_local! {
+	static LAST_REPORT: RefCell<Option<ViolationReport>> = const { RefCell::new(None) };
+}
+
+/// Saves the report and stops the current operation.
+enum RecordTrig {}
+
+impl TrigManuallyDrop for RecordTrig {
+	type Output = Infallible;
+
+	fn trig_next_invalid_beh(a: Arguments<'_>) -> Infallible {
+		panic!("{}", a);
+	}
+
+	fn trig_violation(report: &ViolationReport) -> Infallible {
+		LAST_REPORT.with(|a| *a.borrow_mut() = Some(report.clone()));
+
+		Self::trig_next_invalid_beh(format_args!("{}", report))
+	}
+}
+
+type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, RecordTrig>;
+
+/// Run the function and return the report passed to the trigger.
+fn catch_report(f: impl FnOnce() + std::panic::UnwindSafe) -> ViolationReport {
+	LAST_REPORT.with(|a| *a.borrow_mut() = None);
+	let e = std::panic::catch_unwind(f);
+	assert!(e.is_err());
+
+	LAST_REPORT.with(|a| a.borrow_mut().take()).unwrap()
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_report_kind() {
+	let report = catch_report(|| {
+		let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+		unsafe {
+			ManuallyDrop::drop(&mut data);
+			ManuallyDrop::drop(&mut data);
+		}
+	});
+	assert_eq!(report.kind(), ViolationKind::DoubleDrop);
+	assert_eq!(report.state(), Some(StateManuallyDropData::DropModeTrig));
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Drop));
+	assert_eq!(report.type_name(), std::any::type_name::<Vec<i32>>());
+	#[cfg(feature = "track_location")]
+	assert!(report.release_location().is_some());
+	assert!(report.location().is_some());
+
+	let report = catch_report(|| {
+		let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+		let _value = unsafe { ManuallyDrop::take(&mut data) };
+		let _len = data.len();
+	});
+	assert_eq!(report.kind(), ViolationKind::UseAfterTake);
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Deref));
+	assert_ne!(report.address(), 0);
+
+	// the container is moved into into_inner, the address is unknown
+	let report = catch_report(|| {
+		let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+		unsafe {
+			ManuallyDrop::drop(&mut data);
+		}
+		let _value = ManuallyDrop::into_inner(data);
+	});
+	assert_eq!(report.kind(), ViolationKind::TakeAfterDrop);
+	assert_eq!(report.address(), 0);
+
+	let report = catch_report(|| {
+		let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+		let _value = unsafe { ManuallyDrop::take(&mut data) };
+		let _old = ManuallyDrop::replace(&mut data, vec![4]);
+	});
+	assert_eq!(report.kind(), ViolationKind::DoubleTake);
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Replace));
+
+	let report = catch_report(|| {
+		let data = ManuallyDrop::new(vec![1, 2, 3]);
+		drop(data);
+	});
+	assert_eq!(report.kind(), ViolationKind::ImplicitDrop);
+	assert_eq!(report.state(), Some(StateManuallyDropData::Empty));
+	assert_eq!(report.location(), None);
+	assert!(report.to_string().starts_with(
+		"Undefined behavior when using ManuallyDrop (expected ManuallyDrop::drop(&mut value)), state should not be default, current state is Empty."
+	));
+}
+
+#[test]
+fn test_operation_index() {
+	for (index, operation) in ManuallyDropOperation::ALL.iter().enumerate() {
+		assert_eq!(operation.index(), index);
+	}
+	assert_eq!(
+		ManuallyDropOperation::ALL[ManuallyDropOperation::Replace.index()],
+		ManuallyDropOperation::Replace
+	);
+}
+
+#[cfg(feature = "backtrace")]
+#[test]
+#[allow(unused_unsafe)]
+fn test_report_backtrace() {
+	let report = catch_report(|| {
+		let mut data = ManuallyDrop::new(vec![1, 2, 3]);
+		unsafe {
+			ManuallyDrop::drop(&mut data);
+			ManuallyDrop::drop(&mut data);
+		}
+	});
+
+	let release_backtrace = report.release_backtrace().unwrap();
+	assert_eq!(
+		release_backtrace.status(),
+		std::backtrace::BacktraceStatus::Captured
+	);
+	assert_eq!(
+		report.backtrace().status(),
+		std::backtrace::BacktraceStatus::Captured
+	);
+
+	let message = report.to_string();
+	assert!(message.contains("first dropped, backtrace:"));
+	assert!(message.contains("used again, backtrace:"));
+}
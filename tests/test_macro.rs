@@ -0,0 +1,81 @@
+#![cfg(feature = "test_macro")]
+
+use SafeManuallyDrop::core::report::ViolationKind;
+use SafeManuallyDrop::core::testing::expect_violation;
+use SafeManuallyDrop::core::trig::record::AlwaysSafeRecordManuallyDrop;
+
+type ManuallyDrop<T> = AlwaysSafeRecordManuallyDrop<T>;
+
+#[SafeManuallyDrop::test]
+#[allow(unused_unsafe)]
+fn test_released() {
+	let mut a = ManuallyDrop::new([1, 2, 3]);
+	let mut b = ManuallyDrop::new([4, 5, 6]);
+	let c = ManuallyDrop::new([7, 8, 9]);
+
+	unsafe {
+		ManuallyDrop::drop(&mut a);
+		assert_eq!(ManuallyDrop::take(&mut b), [4, 5, 6]);
+		c.ignore_drop();
+	}
+}
+
+#[SafeManuallyDrop::test]
+#[allow(unused_unsafe)]
+fn test_result() -> Result<(), std::num::ParseIntError> {
+	let mut a = ManuallyDrop::new("1".parse::<u32>()?);
+	unsafe {
+		ManuallyDrop::drop(&mut a);
+	}
+
+	Ok(())
+}
+
+#[SafeManuallyDrop::test]
+#[allow(unused_unsafe)]
+fn test_released_in_other_thread() {
+	let mut a = ManuallyDrop::new([1, 2, 3]);
+
+	std::thread::spawn(move || unsafe {
+		ManuallyDrop::drop(&mut a);
+	})
+	.join()
+	.unwrap();
+}
+
+#[SafeManuallyDrop::test]
+#[allow(unused_unsafe)]
+fn test_expected_violation_is_not_reported() {
+	let report = expect_violation(|| {
+		let mut a = ManuallyDrop::new([1, 2, 3]);
+		unsafe {
+			ManuallyDrop::drop(&mut a);
+			ManuallyDrop::drop(&mut a);
+		}
+	});
+	assert_eq!(report.kind(), ViolationKind::DoubleDrop);
+}
+
+#[SafeManuallyDrop::test]
+#[should_panic(expected = "1 ManuallyDrop violation(s) detected during the test")]
+#[allow(unused_unsafe)]
+fn test_violation_with_nonfatal_trig() {
+	let mut a = ManuallyDrop::new([1, 2, 3]);
+
+	unsafe {
+		ManuallyDrop::drop(&mut a);
+		ManuallyDrop::drop(&mut a); // <-- recorded, skipped
+	}
+}
+
+#[SafeManuallyDrop::test]
+#[should_panic(expected = "1 of 2 ManuallyDrop created during the test were neither released")]
+#[allow(unused_unsafe)]
+fn test_leak() {
+	let mut a = ManuallyDrop::new([1, 2, 3]);
+	unsafe {
+		ManuallyDrop::drop(&mut a);
+	}
+
+	std::mem::forget(ManuallyDrop::new([4, 5, 6]));
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "std")]
+
+use std::panic::catch_unwind;
+use SafeManuallyDrop::core::report::{ManuallyDropOperation, ViolationKind};
+use SafeManuallyDrop::core::testing::{expect_no_violation, expect_violation};
+use SafeManuallyDrop::AlwaysSafeEmptyLoopManuallyDrop;
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_expect_violation() {
+	let report = expect_violation(|| {
+		let mut data = AlwaysSafeEmptyLoopManuallyDrop::new(vec![1, 2, 3]);
+		unsafe {
+			let _value = AlwaysSafeEmptyLoopManuallyDrop::take(&mut data);
+		}
+
+		let _len = data.len(); // <-- stopped here
+		unreachable!();
+	});
+	assert_eq!(report.kind(), ViolationKind::UseAfterTake);
+	assert_eq!(report.operation(), Some(ManuallyDropOperation::Deref));
+
+	// implicit drop (in Drop)
+	let report = expect_violation(|| {
+		let _data = AlwaysSafeEmptyLoopManuallyDrop::new(vec![1, 2, 3]);
+	});
+	assert_eq!(report.kind(), ViolationKind::ImplicitDrop);
+
+	// nested
+	let report = expect_violation(|| {
+		let inner = expect_violation(|| {
+			let mut data = AlwaysSafeEmptyLoopManuallyDrop::new(1);
+			unsafe {
+				AlwaysSafeEmptyLoopManuallyDrop::drop(&mut data);
+				AlwaysSafeEmptyLoopManuallyDrop::drop(&mut data);
+			}
+		});
+		assert_eq!(inner.kind(), ViolationKind::DoubleDrop);
+
+		let mut data = AlwaysSafeEmptyLoopManuallyDrop::new(1);
+		unsafe {
+			AlwaysSafeEmptyLoopManuallyDrop::drop(&mut data);
+			let _value = AlwaysSafeEmptyLoopManuallyDrop::take(&mut data);
+		}
+	});
+	assert_eq!(report.kind(), ViolationKind::TakeAfterDrop);
+}
+
+#[cfg(feature = "support_panic_trig")]
+#[test]
+#[allow(unused_unsafe)]
+fn test_expect_violation_panic_trig() {
+	use SafeManuallyDrop::AlwaysSafePanicManuallyDrop;
+
+	let report = expect_violation(|| {
+		let mut data = AlwaysSafePanicManuallyDrop::new(1);
+		unsafe {
+			AlwaysSafePanicManuallyDrop::drop(&mut data);
+			AlwaysSafePanicManuallyDrop::drop(&mut data);
+		}
+	});
+	assert_eq!(report.kind(), ViolationKind::DoubleDrop);
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_expect_no_violation() {
+	let value = expect_no_violation(|| {
+		let mut data = AlwaysSafeEmptyLoopManuallyDrop::new(vec![1, 2, 3]);
+		unsafe { AlwaysSafeEmptyLoopManuallyDrop::take(&mut data) }
+	});
+	assert_eq!(value, vec![1, 2, 3]);
+
+	let e = catch_unwind(|| {
+		expect_no_violation(|| {
+			let _data = AlwaysSafeEmptyLoopManuallyDrop::new(1);
+		})
+	});
+	assert!(e.is_err());
+
+	// a panic of the body is not a violation
+	let e = catch_unwind(|| expect_violation(|| panic!("body")));
+	assert_eq!(*e.unwrap_err().downcast::<&str>().unwrap(), "body");
+}
+
+#[test]
+#[allow(unused_unsafe)]
+fn test_violation_while_unwinding() {
+	use core::fmt::Arguments;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use SafeManuallyDrop::core::trig::TrigManuallyDrop;
+	use SafeManuallyDrop::AlwaysSafeManuallyDrop;
+
+	static TRIGS: AtomicUsize = AtomicUsize::new(0);
+
+	enum CountTrig {}
+	impl TrigManuallyDrop for CountTrig {
+		type Output = ();
+
+		fn trig_next_invalid_beh(_a: Arguments<'_>) {
+			TRIGS.fetch_add(1, Ordering::SeqCst);
+		}
+	}
+	type ManuallyDrop<T> = AlwaysSafeManuallyDrop<T, CountTrig>;
+
+	/// Touches the released value while the thread is unwinding.
+	struct DropReleased(ManuallyDrop<Vec<i32>>);
+	impl Drop for DropReleased {
+		fn drop(&mut self) {
+			unsafe {
+				ManuallyDrop::drop(&mut self.0); // <-- double drop, skipped
+			}
+		}
+	}
+
+	// the violation is not captured, the trigger is executed instead of unwinding again
+	let e = catch_unwind(|| {
+		expect_violation(|| {
+			let mut data = DropReleased(ManuallyDrop::new(vec![1, 2, 3]));
+			unsafe {
+				ManuallyDrop::drop(&mut data.0);
+			}
+
+			panic!("body");
+		})
+	});
+	assert_eq!(*e.unwrap_err().downcast::<&str>().unwrap(), "body");
+	assert_eq!(TRIGS.load(Ordering::SeqCst), 1);
+}